@@ -0,0 +1,140 @@
+use crate::graph::{EdgeKind, Graph, Node, NodeKind};
+use crate::package::TargetType;
+use std::collections::HashMap;
+use std::fmt::{self, Write};
+
+/// Renders the graph as D2 source. External products are placed in a container per package, as
+/// are the package's own nodes when `clusters` is set.
+pub fn render(graph: &Graph, clusters: bool) -> String {
+    let mut out = String::new();
+    write_diagram(&mut out, graph, clusters).expect("formatting into a String cannot fail");
+    out
+}
+
+fn write_diagram(out: &mut String, graph: &Graph, clusters: bool) -> fmt::Result {
+    writeln!(out, "direction: down")?;
+
+    // Nodes inside a container are referenced by their full path from edges.
+    let mut paths: HashMap<&str, String> = HashMap::new();
+
+    let local_container = clusters.then(|| key(&graph.name));
+    if let Some(container) = &local_container {
+        writeln!(out, "{}: {{", container)?;
+    }
+    for node in graph.local_nodes() {
+        paths.insert(&node.id, path(local_container.as_deref(), node));
+        write_node(out, node, local_container.is_some())?;
+    }
+    if local_container.is_some() {
+        writeln!(out, "}}")?;
+    }
+
+    for package in &graph.packages {
+        let container = key(&package.identity);
+        writeln!(out, "{}: {{", container)?;
+        if let Some(state) = &package.state {
+            writeln!(
+                out,
+                "  label: {}",
+                key(&format!("{} {}", package.identity, state))
+            )?;
+        }
+        for node in graph.external_products(Some(&package.identity)) {
+            paths.insert(&node.id, path(Some(&container), node));
+            write_node(out, node, true)?;
+        }
+        writeln!(out, "}}")?;
+    }
+    // Products whose package could not be resolved are not grouped.
+    for node in graph.external_products(None) {
+        paths.insert(&node.id, path(None, node));
+        write_node(out, node, false)?;
+    }
+
+    for edge in &graph.edges {
+        let from = paths
+            .get(edge.from.as_str())
+            .cloned()
+            .unwrap_or(key(&edge.from));
+        let to = paths
+            .get(edge.to.as_str())
+            .cloned()
+            .unwrap_or(key(&edge.to));
+        let mut styles = Vec::new();
+        if edge.redundant || edge.kind == EdgeKind::Vends {
+            styles.push("style.stroke-dash: 3");
+        }
+        if edge.in_cycle {
+            styles.push("style.stroke: \"#DC2626\"");
+        } else if edge.redundant {
+            styles.push("style.stroke: \"#9CA3AF\"");
+        }
+        write!(out, "{} -> {}: {}", from, to, edge.kind.name())?;
+        if !styles.is_empty() {
+            write!(out, " {{ {} }}", styles.join("; "))?;
+        }
+        writeln!(out)?;
+    }
+    Ok(())
+}
+
+fn write_node(out: &mut String, node: &Node, nested: bool) -> fmt::Result {
+    let indent = if nested { "  " } else { "" };
+    let (label, shape, fill) = match &node.kind {
+        NodeKind::Target(target_type) => (
+            node.name.clone(),
+            target_shape(target_type),
+            target_fill(target_type),
+        ),
+        NodeKind::Product(product_type) => (
+            format!("{} ({})", node.name, product_type.description()),
+            "package",
+            "#DCFCE7",
+        ),
+        NodeKind::ExternalProduct { .. } => (node.name.clone(), "rectangle", "#E0F2FE"),
+    };
+    writeln!(out, "{}{}: {} {{", indent, key(&node.id), key(&label))?;
+    writeln!(out, "{}  shape: {}", indent, shape)?;
+    writeln!(out, "{}  style.fill: \"{}\"", indent, fill)?;
+    writeln!(out, "{}}}", indent)
+}
+
+fn path(container: Option<&str>, node: &Node) -> String {
+    match container {
+        Some(container) => format!("{}.{}", container, key(&node.id)),
+        None => key(&node.id),
+    }
+}
+
+fn target_shape(target_type: &TargetType) -> &'static str {
+    match target_type {
+        TargetType::Executable => "hexagon",
+        TargetType::Library => "rectangle",
+        TargetType::Macro => "diamond",
+        TargetType::Test => "page",
+        TargetType::Plugin => "parallelogram",
+        TargetType::SystemTarget => "cylinder",
+        TargetType::Binary => "stored_data",
+        TargetType::Snippet => "document",
+        TargetType::Unknown => "oval",
+    }
+}
+
+fn target_fill(target_type: &TargetType) -> &'static str {
+    match target_type {
+        TargetType::Executable => "#FEF3C7",
+        TargetType::Library => "#DBEAFE",
+        TargetType::Macro => "#EDE9FE",
+        TargetType::Test => "#F3F4F6",
+        TargetType::Plugin => "#FCE7F3",
+        TargetType::SystemTarget | TargetType::Binary => "#E5E7EB",
+        TargetType::Snippet => "#ECFCCB",
+        TargetType::Unknown => "#FFFFFF",
+    }
+}
+
+/// Quotes a key or label. Names like `product:Foo` or `swift-collections` would otherwise be read
+/// as D2 syntax.
+fn key(text: &str) -> String {
+    format!("\"{}\"", text.replace('\\', "\\\\").replace('"', "\\\""))
+}
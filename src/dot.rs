@@ -0,0 +1,152 @@
+use crate::error::{Error, Result};
+use crate::format::LayoutEngine;
+use crate::graph::{EdgeKind, Graph, Node, NodeKind};
+use crate::package::{ProductType, TargetType};
+use tabbycat::attributes::*;
+use tabbycat::{AttrList, AttrType, Edge, GraphBuilder, GraphType, Identity, StmtList, SubGraph};
+
+#[derive(Debug, Default)]
+pub struct DotOptions {
+    /// Wrap the package's own nodes, and each external package's products, in labelled clusters.
+    pub clusters: bool,
+    pub layout_engine: Option<LayoutEngine>,
+}
+
+pub fn render(graph: &Graph, options: &DotOptions) -> Result<String> {
+    let mut statements = StmtList::new();
+
+    for edge in &graph.edges {
+        let mut dot_edge = Edge::head_node(Identity::quoted(&edge.from), None)
+            .arrow_to_node(Identity::quoted(&edge.to), None);
+        if edge.kind == EdgeKind::Vends {
+            dot_edge = dot_edge.add_attrpair(style(Style::Dashed));
+        }
+        if edge.redundant {
+            dot_edge = dot_edge
+                .add_attrpair(style(Style::Dashed))
+                .add_attrpair(color(Color::Gray));
+        }
+        if edge.in_cycle {
+            dot_edge = dot_edge.add_attrpair(color(Color::Red));
+        }
+        statements = statements.add_edge(dot_edge);
+    }
+
+    let mut local_statements = StmtList::new();
+    for node in graph.local_nodes() {
+        local_statements = add_node(local_statements, node, options);
+    }
+    statements = if options.clusters {
+        statements.add_subgraph(SubGraph::subgraph(
+            Some(Identity::quoted(format!("cluster_{}", graph.name))),
+            local_statements.add_attr(
+                AttrType::Graph,
+                AttrList::new().add_pair(label(&graph.name)),
+            ),
+        ))
+    } else {
+        statements.extend(local_statements)
+    };
+
+    // Products whose package could not be resolved are not grouped.
+    for node in graph.external_products(None) {
+        statements = add_node(statements, node, options);
+    }
+    for package in &graph.packages {
+        let mut package_statements = StmtList::new();
+        for node in graph.external_products(Some(&package.identity)) {
+            package_statements = add_node(package_statements, node, options);
+        }
+        statements = if options.clusters {
+            let cluster_label = match &package.state {
+                Some(state) => format!("{}\n{}", package.identity, state),
+                None => package.identity.clone(),
+            };
+            statements.add_subgraph(SubGraph::subgraph(
+                Some(Identity::quoted(format!("cluster_{}", package.identity))),
+                package_statements.add_attr(
+                    AttrType::Graph,
+                    AttrList::new()
+                        .add_pair(label(cluster_label))
+                        .add_pair(color(Color::Blue)),
+                ),
+            ))
+        } else {
+            // Keep the products of each external package side by side.
+            statements.add_subgraph(SubGraph::subgraph(
+                Some(Identity::quoted(format!("package:{}", package.identity))),
+                package_statements.add_attr(
+                    AttrType::Graph,
+                    AttrList::new().add_pair(rank(RankType::Same)),
+                ),
+            ))
+        };
+    }
+
+    // Record the engine in DOT output too, so it is used when the file is rendered later.
+    if let Some(layout_engine) = options.layout_engine {
+        statements = statements.add_attr(
+            AttrType::Graph,
+            AttrList::new().add_pair(layout(layout_engine.to_string())),
+        );
+    }
+
+    let graph = GraphBuilder::default()
+        .graph_type(GraphType::DiGraph)
+        .strict(false)
+        .id(Identity::quoted(&graph.name))
+        .stmts(statements)
+        .build()
+        .map_err(Error::Graph)?;
+    Ok(graph.to_string())
+}
+
+fn add_node(statements: StmtList, node: &Node, options: &DotOptions) -> StmtList {
+    let attributes = match &node.kind {
+        NodeKind::Target(target_type) => AttrList::new()
+            .add_pair(color(Color::Black))
+            .add_pair(shape(target_shape(target_type))),
+        NodeKind::Product(product_type) => AttrList::new()
+            .add_pair(label(format!(
+                "{}\n({})",
+                node.name,
+                product_type.description()
+            )))
+            .add_pair(color(Color::Darkgreen))
+            .add_pair(shape(product_shape(product_type))),
+        NodeKind::ExternalProduct { package } => {
+            // The cluster label already names the package.
+            let text = match (package, options.clusters) {
+                (Some(package), false) => format!("{}\n({})", node.name, package),
+                _ => node.name.clone(),
+            };
+            AttrList::new()
+                .add_pair(label(text))
+                .add_pair(color(Color::Blue))
+                .add_pair(shape(Shape::Box))
+        }
+    };
+    statements.add_node(Identity::quoted(&node.id), None, Some(attributes))
+}
+
+fn target_shape(target_type: &TargetType) -> Shape {
+    match target_type {
+        TargetType::Executable => Shape::Box3d,
+        TargetType::Library => Shape::Box,
+        TargetType::Macro => Shape::Hexagon,
+        TargetType::Test => Shape::Note,
+        TargetType::Plugin => Shape::Component,
+        TargetType::SystemTarget => Shape::Cylinder,
+        TargetType::Binary => Shape::Folder,
+        TargetType::Snippet => Shape::Tab,
+        TargetType::Unknown => Shape::Ellipse,
+    }
+}
+
+fn product_shape(product_type: &ProductType) -> Shape {
+    match product_type {
+        ProductType::Library(_) => Shape::Invhouse,
+        ProductType::Executable => Shape::House,
+        _ => Shape::Invtrapezium,
+    }
+}
@@ -0,0 +1,138 @@
+use std::fmt;
+use std::io;
+use std::path::PathBuf;
+use std::process::ExitStatus;
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+#[derive(Debug)]
+pub enum Error {
+    /// The output file's extension does not name a supported format.
+    UnknownFormat(String),
+    /// No target or product has the name given on the command line.
+    UnknownNode(String),
+    /// The package directory does not exist.
+    PackageNotFound(PathBuf),
+    /// An input file (or stdin) could not be read.
+    ReadInput {
+        path: PathBuf,
+        source: io::Error,
+    },
+    /// Input could not be parsed; `what` names the input, e.g. the file it came from.
+    InvalidJson {
+        what: String,
+        source: serde_json::Error,
+    },
+    SwiftNotFound,
+    Swift(io::Error),
+    /// A `swift package` command exited unsuccessfully.
+    SwiftFailed {
+        command: String,
+        status: ExitStatus,
+        stderr: String,
+    },
+    GraphvizNotFound,
+    Graphviz(io::Error),
+    /// `dot` exited unsuccessfully.
+    GraphvizFailed {
+        status: ExitStatus,
+        stderr: String,
+    },
+    /// The output file (or stdout) could not be written.
+    WriteOutput {
+        destination: String,
+        source: io::Error,
+    },
+    /// The DOT graph could not be assembled.
+    Graph(String),
+    /// `check-cycles` found this many groups of nodes depending on each other.
+    Cycles(usize),
+    /// `lint` found this many dependencies breaking the rules.
+    RuleViolations(usize),
+}
+
+impl Error {
+    /// The process exit code for this error. Each class of failure gets its own code so scripts
+    /// can react to them; 2 is shared with clap for usage errors.
+    pub fn exit_code(&self) -> u8 {
+        match self {
+            Error::Graph(_) => 1,
+            Error::UnknownFormat(_) | Error::UnknownNode(_) => 2,
+            Error::PackageNotFound(_) | Error::ReadInput { .. } => 3,
+            Error::InvalidJson { .. } => 4,
+            Error::SwiftNotFound | Error::Swift(_) | Error::SwiftFailed { .. } => 5,
+            Error::GraphvizNotFound | Error::Graphviz(_) | Error::GraphvizFailed { .. } => 6,
+            Error::WriteOutput { .. } => 7,
+            Error::Cycles(_) => 8,
+            Error::RuleViolations(_) => 9,
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::UnknownFormat(extension) => write!(
+                f,
+                "unknown output format `{}`, use --format to choose one",
+                extension
+            ),
+            Error::UnknownNode(name) => write!(f, "no target or product named `{}`", name),
+            Error::PackageNotFound(path) => {
+                write!(f, "package directory {} does not exist", path.display())
+            }
+            Error::ReadInput { path, source } => {
+                write!(f, "could not read {}: {}", path.display(), source)
+            }
+            Error::InvalidJson { what, source } => write!(f, "invalid {}: {}", what, source),
+            Error::SwiftNotFound => {
+                write!(f, "could not find `swift`, is a Swift toolchain installed?")
+            }
+            Error::Swift(source) => write!(f, "could not run `swift`: {}", source),
+            Error::SwiftFailed {
+                command,
+                status,
+                stderr,
+            } => write_failure(f, command, status, stderr),
+            Error::GraphvizNotFound => write!(f, "could not find `dot`, is graphviz installed?"),
+            Error::Graphviz(source) => write!(f, "could not run `dot`: {}", source),
+            Error::GraphvizFailed { status, stderr } => write_failure(f, "dot", status, stderr),
+            Error::WriteOutput {
+                destination,
+                source,
+            } => write!(f, "could not write {}: {}", destination, source),
+            Error::Graph(message) => write!(f, "could not build graph: {}", message),
+            Error::Cycles(1) => write!(f, "found a dependency cycle"),
+            Error::Cycles(count) => write!(f, "found {} dependency cycles", count),
+            Error::RuleViolations(1) => write!(f, "found a dependency breaking the rules"),
+            Error::RuleViolations(count) => {
+                write!(f, "found {} dependencies breaking the rules", count)
+            }
+        }
+    }
+}
+
+/// Describes a failed command, followed by whatever it printed to stderr.
+fn write_failure(
+    f: &mut fmt::Formatter<'_>,
+    command: &str,
+    status: &ExitStatus,
+    stderr: &str,
+) -> fmt::Result {
+    write!(f, "`{}` failed ({})", command, status)?;
+    if !stderr.trim().is_empty() {
+        write!(f, ":\n{}", stderr.trim_end())?;
+    }
+    Ok(())
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::ReadInput { source, .. } | Error::WriteOutput { source, .. } => Some(source),
+            Error::Swift(source) | Error::Graphviz(source) => Some(source),
+            Error::InvalidJson { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
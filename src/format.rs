@@ -0,0 +1,141 @@
+use clap::ValueEnum;
+use std::fmt;
+use std::str::FromStr;
+
+/// Output formats rendered by piping the DOT source through Graphviz (`dot -T<format>`). SVG is
+/// rendered without Graphviz unless asked for.
+const GRAPHVIZ_FORMATS: &[&str] = &[
+    "bmp",
+    "canon",
+    "cmap",
+    "cmapx",
+    "dot_json",
+    "eps",
+    "fig",
+    "gd",
+    "gd2",
+    "gif",
+    "ico",
+    "imap",
+    "jpe",
+    "jpeg",
+    "jpg",
+    "json0",
+    "pdf",
+    "pic",
+    "plain",
+    "plain-ext",
+    "png",
+    "ps",
+    "ps2",
+    "svgz",
+    "tif",
+    "tiff",
+    "vml",
+    "vrml",
+    "wbmp",
+    "webp",
+    "xdot",
+    "xdot1.2",
+    "xdot1.4",
+    "xdot_json",
+];
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Format {
+    /// DOT source, written as-is without running Graphviz.
+    Dot,
+    /// Any of the `GRAPHVIZ_FORMATS`.
+    Graphviz(String),
+    /// A Mermaid flowchart, which GitHub renders in Markdown.
+    Mermaid,
+    /// A PlantUML component diagram.
+    PlantUml,
+    /// D2 diagram source.
+    D2,
+    GraphMl,
+    Gexf,
+    /// The graph model as JSON, see `json::render`.
+    Json,
+    /// A self-contained interactive viewer page.
+    Html,
+    /// SVG laid out by this tool, see `svg::render`.
+    Svg,
+    /// Drawn with Unicode box-drawing characters, for viewing in a terminal.
+    Terminal,
+    /// Drawn with ASCII characters only.
+    Ascii,
+}
+
+impl Format {
+    /// Infers the format from an output file's extension.
+    pub fn from_extension(extension: &str) -> Option<Self> {
+        extension.to_lowercase().parse().ok()
+    }
+
+    /// The extension for output files of this format when no output path is given.
+    pub fn extension(&self) -> &str {
+        match self {
+            Format::Dot => "dot",
+            Format::Mermaid => "mmd",
+            Format::PlantUml => "puml",
+            Format::D2 => "d2",
+            Format::GraphMl => "graphml",
+            Format::Gexf => "gexf",
+            Format::Json => "json",
+            Format::Html => "html",
+            Format::Svg => "svg",
+            Format::Terminal | Format::Ascii => "txt",
+            Format::Graphviz(name) => match name.as_str() {
+                "json0" | "dot_json" | "xdot_json" => "json",
+                "xdot1.2" | "xdot1.4" => "xdot",
+                "plain" | "plain-ext" => "txt",
+                name => name,
+            },
+        }
+    }
+}
+
+impl FromStr for Format {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        match name {
+            "dot" | "gv" => Ok(Format::Dot),
+            "mermaid" | "mmd" => Ok(Format::Mermaid),
+            "plantuml" | "puml" | "pu" => Ok(Format::PlantUml),
+            "d2" => Ok(Format::D2),
+            "graphml" => Ok(Format::GraphMl),
+            "gexf" => Ok(Format::Gexf),
+            "json" => Ok(Format::Json),
+            "html" | "htm" => Ok(Format::Html),
+            "svg" => Ok(Format::Svg),
+            "terminal" | "term" => Ok(Format::Terminal),
+            "ascii" => Ok(Format::Ascii),
+            name if GRAPHVIZ_FORMATS.contains(&name) => Ok(Format::Graphviz(name.to_string())),
+            name => Err(format!(
+                "unknown format `{}`, expected one of: dot, mermaid, plantuml, d2, graphml, gexf, json, html, svg, terminal, ascii, {}",
+                name,
+                GRAPHVIZ_FORMATS.join(", ")
+            )),
+        }
+    }
+}
+
+/// The Graphviz layout engines, passed to `dot` as `-K<engine>`.
+#[derive(Clone, Copy, Debug, PartialEq, ValueEnum)]
+pub enum LayoutEngine {
+    Dot,
+    Neato,
+    Fdp,
+    Sfdp,
+    Circo,
+    Twopi,
+}
+
+impl fmt::Display for LayoutEngine {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = self.to_possible_value().expect("no variants are skipped");
+        write!(f, "{}", name.get_name())
+    }
+}
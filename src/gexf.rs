@@ -0,0 +1,91 @@
+use crate::graph::Graph;
+use crate::xml::{edge_attributes, escape, node_attributes, EDGE_ATTRIBUTES, NODE_ATTRIBUTES};
+use std::fmt::{self, Write};
+
+/// Renders the graph as GEXF 1.3, e.g. for Gephi.
+pub fn render(graph: &Graph) -> String {
+    let mut out = String::new();
+    write_gexf(&mut out, graph).expect("formatting into a String cannot fail");
+    out
+}
+
+fn write_gexf(out: &mut String, graph: &Graph) -> fmt::Result {
+    writeln!(out, r#"<?xml version="1.0" encoding="UTF-8"?>"#)?;
+    writeln!(out, r#"<gexf xmlns="http://gexf.net/1.3" version="1.3">"#)?;
+    writeln!(out, "  <meta>")?;
+    writeln!(out, "    <creator>spm_to_graph</creator>")?;
+    writeln!(
+        out,
+        "    <description>{}</description>",
+        escape(&graph.name)
+    )?;
+    writeln!(out, "  </meta>")?;
+    writeln!(out, r#"  <graph defaultedgetype="directed" mode="static">"#)?;
+    write_attributes(out, "node", NODE_ATTRIBUTES)?;
+    write_attributes(out, "edge", EDGE_ATTRIBUTES)?;
+
+    writeln!(out, "    <nodes>")?;
+    for node in &graph.nodes {
+        writeln!(
+            out,
+            r#"      <node id="{}" label="{}">"#,
+            escape(&node.id),
+            escape(&node.name)
+        )?;
+        writeln!(out, "        <attvalues>")?;
+        for (name, value) in node_attributes(graph, node) {
+            writeln!(
+                out,
+                r#"          <attvalue for="{}" value="{}"/>"#,
+                name,
+                escape(&value)
+            )?;
+        }
+        writeln!(out, "        </attvalues>")?;
+        writeln!(out, "      </node>")?;
+    }
+    for id in graph.missing_nodes() {
+        writeln!(out, r#"      <node id="{0}" label="{0}"/>"#, escape(id))?;
+    }
+    writeln!(out, "    </nodes>")?;
+
+    writeln!(out, "    <edges>")?;
+    for (index, edge) in graph.edges.iter().enumerate() {
+        writeln!(
+            out,
+            r#"      <edge id="{}" source="{}" target="{}">"#,
+            index,
+            escape(&edge.from),
+            escape(&edge.to)
+        )?;
+        writeln!(out, "        <attvalues>")?;
+        for (name, value) in edge_attributes(edge) {
+            writeln!(
+                out,
+                r#"          <attvalue for="{}" value="{}"/>"#,
+                name, value
+            )?;
+        }
+        writeln!(out, "        </attvalues>")?;
+        writeln!(out, "      </edge>")?;
+    }
+    writeln!(out, "    </edges>")?;
+    writeln!(out, "  </graph>")?;
+    writeln!(out, "</gexf>")
+}
+
+fn write_attributes(out: &mut String, class: &str, attributes: &[(&str, &str)]) -> fmt::Result {
+    writeln!(out, r#"    <attributes class="{}">"#, class)?;
+    for (name, attribute_type) in attributes {
+        let attribute_type = match *attribute_type {
+            "int" => "integer",
+            other => other,
+        };
+        writeln!(
+            out,
+            r#"      <attribute id="{0}" title="{0}" type="{1}"/>"#,
+            name, attribute_type
+        )?;
+    }
+    writeln!(out, "    </attributes>")
+}
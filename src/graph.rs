@@ -0,0 +1,946 @@
+use crate::manifest::{Condition, ManifestDependencies, Resolved};
+use crate::package::{Package, ProductType, TargetType};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet, VecDeque};
+
+/// The package's targets, products and their dependencies, independent of any output format.
+/// Every renderer works from this rather than from the `swift package describe` output.
+#[derive(Debug)]
+pub struct Graph {
+    pub name: String,
+    pub nodes: Vec<Node>,
+    pub edges: Vec<Edge>,
+    /// The external packages providing the graph's external products, by identity.
+    pub packages: Vec<ExternalPackage>,
+}
+
+#[derive(Debug)]
+pub struct Node {
+    /// Unique within the graph. Targets use their name, products and external products are
+    /// namespaced as they commonly share a name with a target.
+    pub id: String,
+    pub name: String,
+    pub kind: NodeKind,
+    /// A target's directory, relative to the package.
+    pub path: Option<String>,
+    /// The number of source files in a target.
+    pub source_count: Option<usize>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum NodeKind {
+    Target(TargetType),
+    Product(ProductType),
+    /// A product of another package; `package` is the identity of the package providing it, if
+    /// it could be resolved.
+    ExternalProduct {
+        package: Option<String>,
+    },
+}
+
+impl NodeKind {
+    pub fn name(&self) -> &'static str {
+        match self {
+            NodeKind::Target(_) => "target",
+            NodeKind::Product(_) => "product",
+            NodeKind::ExternalProduct { .. } => "external-product",
+        }
+    }
+
+    /// The target type or product type, e.g. `library` or `dynamic library`. External products
+    /// have none.
+    pub fn type_name(&self) -> Option<&'static str> {
+        match self {
+            NodeKind::Target(target_type) => Some(target_type.name()),
+            NodeKind::Product(product_type) => Some(product_type.description()),
+            NodeKind::ExternalProduct { .. } => None,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct Edge {
+    pub from: String,
+    pub to: String,
+    pub kind: EdgeKind,
+    /// The platforms or configuration the dependency is restricted to, if any.
+    pub condition: Option<Condition>,
+    /// Set for dependencies implied by other paths, kept by `transitive_reduction` for display.
+    pub redundant: bool,
+    /// Set by `mark_cycles` for edges between nodes that depend on each other.
+    pub in_cycle: bool,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum EdgeKind {
+    /// A target depending on another target of the package.
+    Target,
+    /// A target depending on an external product.
+    Product,
+    /// A product containing one of the package's targets.
+    Vends,
+}
+
+impl EdgeKind {
+    pub fn name(&self) -> &'static str {
+        match self {
+            EdgeKind::Target => "target",
+            EdgeKind::Product => "product",
+            EdgeKind::Vends => "vends",
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct ExternalPackage {
+    pub identity: String,
+    /// What the package is pinned to in `Package.resolved`, see `Pin::state`.
+    pub state: Option<String>,
+}
+
+/// What to leave out of the graph.
+#[derive(Debug, Default)]
+pub struct GraphOptions {
+    pub skip_test_targets: bool,
+    pub skip_product_dependencies: bool,
+    pub skip_products: bool,
+}
+
+/// Nodes that all depend on each other, directly or indirectly: a strongly connected component of
+/// the graph.
+#[derive(Debug)]
+pub struct Cycle {
+    /// The ids of the nodes involved, in graph order.
+    pub nodes: Vec<String>,
+    /// One of the shortest cycles through the first node, starting and ending with it.
+    pub path: Vec<String>,
+}
+
+/// Which way to walk from the node a graph is focused on.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Direction {
+    /// What the node depends on.
+    Dependencies,
+    /// What depends on the node.
+    Dependents,
+    Both,
+}
+
+impl Graph {
+    pub fn new(
+        package: &Package,
+        manifest_dependencies: &ManifestDependencies,
+        resolved: &Resolved,
+        options: &GraphOptions,
+    ) -> Self {
+        let mut nodes = Vec::new();
+        let mut edges = Vec::new();
+
+        if !options.skip_products {
+            for product in &package.products {
+                if options.skip_test_targets && product.product_type == ProductType::Test {
+                    continue;
+                }
+                let id = format!("product:{}", product.name);
+                for target in &product.targets {
+                    edges.push(Edge {
+                        from: id.clone(),
+                        to: target.clone(),
+                        kind: EdgeKind::Vends,
+                        condition: None,
+                        redundant: false,
+                        in_cycle: false,
+                    });
+                }
+                nodes.push(Node {
+                    id,
+                    name: product.name.clone(),
+                    kind: NodeKind::Product(product.product_type.clone()),
+                    path: None,
+                    source_count: None,
+                });
+            }
+        }
+
+        // External products keyed by the identity of the package providing them, if known.
+        let mut external_products: BTreeMap<Option<String>, BTreeSet<String>> = BTreeMap::new();
+
+        for target in &package.targets {
+            if options.skip_test_targets && target.target_type == TargetType::Test {
+                continue;
+            }
+            nodes.push(Node {
+                id: target.name.clone(),
+                name: target.name.clone(),
+                kind: NodeKind::Target(target.target_type),
+                path: target.path.clone(),
+                source_count: Some(target.sources.len()),
+            });
+
+            for target_dependency in target.target_dependencies.iter().flatten() {
+                edges.push(Edge {
+                    from: target.name.clone(),
+                    to: target_dependency.clone(),
+                    kind: EdgeKind::Target,
+                    condition: manifest_dependencies
+                        .condition_for(&target.name, target_dependency)
+                        .cloned(),
+                    redundant: false,
+                    in_cycle: false,
+                });
+            }
+            if !options.skip_product_dependencies {
+                for product_dependency in target.product_dependencies.iter().flatten() {
+                    let package = manifest_dependencies
+                        .package_for(&target.name, product_dependency)
+                        .map(str::to_string);
+                    edges.push(Edge {
+                        from: target.name.clone(),
+                        to: external_product_id(package.as_deref(), product_dependency),
+                        kind: EdgeKind::Product,
+                        condition: manifest_dependencies
+                            .condition_for(&target.name, product_dependency)
+                            .cloned(),
+                        redundant: false,
+                        in_cycle: false,
+                    });
+                    external_products
+                        .entry(package)
+                        .or_default()
+                        .insert(product_dependency.clone());
+                }
+            }
+        }
+
+        let mut packages = Vec::new();
+        for (package, products) in external_products {
+            for product in products {
+                nodes.push(Node {
+                    id: external_product_id(package.as_deref(), &product),
+                    name: product,
+                    kind: NodeKind::ExternalProduct {
+                        package: package.clone(),
+                    },
+                    path: None,
+                    source_count: None,
+                });
+            }
+            if let Some(identity) = package {
+                packages.push(ExternalPackage {
+                    state: resolved.pin(&identity).and_then(|pin| pin.state()),
+                    identity,
+                });
+            }
+        }
+
+        Graph {
+            name: package.name.clone(),
+            nodes,
+            edges,
+            packages,
+        }
+    }
+
+    /// The node with the given id or, failing that, name.
+    pub fn find(&self, name: &str) -> Option<&Node> {
+        self.nodes
+            .iter()
+            .find(|node| node.id == name)
+            .or_else(|| self.nodes.iter().find(|node| node.name == name))
+    }
+
+    /// Reduces the graph to the nodes reachable from `id` in `direction` within `depth` edges, or
+    /// any number of edges if `depth` is `None`. With `Direction::Both`, dependencies and
+    /// dependents are searched separately, so e.g. the other dependents of a dependency are left
+    /// out.
+    pub fn focus(&mut self, id: &str, depth: Option<usize>, direction: Direction) {
+        let mut reached: HashSet<String> = HashSet::from([id.to_string()]);
+        if direction != Direction::Dependents {
+            reached.extend(self.reachable(id, depth, |edge| (&edge.from, &edge.to)));
+        }
+        if direction != Direction::Dependencies {
+            reached.extend(self.reachable(id, depth, |edge| (&edge.to, &edge.from)));
+        }
+        self.retain(|id| reached.contains(id));
+    }
+
+    /// The ids reached from `id` within `depth` edges, following each edge from the first id
+    /// `ends` returns for it to the second.
+    fn reachable(
+        &self,
+        id: &str,
+        depth: Option<usize>,
+        ends: impl Fn(&Edge) -> (&String, &String),
+    ) -> HashSet<String> {
+        let mut reached = HashSet::new();
+        let mut queue = VecDeque::from([(id.to_string(), 0)]);
+        while let Some((current, distance)) = queue.pop_front() {
+            if depth.is_some_and(|depth| distance >= depth) {
+                continue;
+            }
+            for edge in &self.edges {
+                let (from, to) = ends(edge);
+                if *from == current && reached.insert(to.clone()) {
+                    queue.push_back((to.clone(), distance + 1));
+                }
+            }
+        }
+        reached
+    }
+
+    /// Removes the nodes `remove` selects by id and name, including those edges refer to that are
+    /// not part of the graph. With `collapse`, nodes that depended on a removed node depend on its
+    /// dependencies instead, so no path through it is lost.
+    pub fn remove_nodes(&mut self, remove: impl Fn(&str, &str) -> bool, collapse: bool) {
+        let missing = self.missing_nodes();
+        let removed: HashSet<String> = self
+            .nodes
+            .iter()
+            .map(|node| (node.id.as_str(), node.name.as_str()))
+            .chain(missing.into_iter().map(|id| (id, id)))
+            .filter(|(id, name)| remove(id, name))
+            .map(|(id, _)| id.to_string())
+            .collect();
+
+        let mut bridges = Vec::new();
+        if collapse {
+            let existing: HashSet<(&str, &str)> = self
+                .edges
+                .iter()
+                .map(|edge| (edge.from.as_str(), edge.to.as_str()))
+                .collect();
+            let mut added = HashSet::new();
+            for edge in &self.edges {
+                if removed.contains(&edge.from) || !removed.contains(&edge.to) {
+                    continue;
+                }
+                // Walk through removed nodes to the nodes that remain.
+                let mut visited = HashSet::from([edge.to.as_str()]);
+                let mut stack = vec![edge.to.as_str()];
+                while let Some(current) = stack.pop() {
+                    for next in self.edges.iter().filter(|next| next.from == current) {
+                        if removed.contains(&next.to) {
+                            if visited.insert(&next.to) {
+                                stack.push(&next.to);
+                            }
+                        } else if !existing.contains(&(edge.from.as_str(), next.to.as_str()))
+                            && next.to != edge.from
+                            && added.insert((edge.from.clone(), next.to.clone()))
+                        {
+                            bridges.push(Edge {
+                                from: edge.from.clone(),
+                                to: next.to.clone(),
+                                kind: next.kind,
+                                condition: None,
+                                redundant: false,
+                                in_cycle: false,
+                            });
+                        }
+                    }
+                }
+            }
+        }
+
+        self.retain(|id| !removed.contains(id));
+        self.edges.extend(bridges);
+    }
+
+    /// Finds the dependencies implied by other paths, e.g. A → C when A → B → C exists, and removes
+    /// them, or only marks them `redundant` with `keep_redundant`. What each node can reach stays
+    /// the same. Edges from products to the targets they contain are left alone.
+    pub fn transitive_reduction(&mut self, keep_redundant: bool) {
+        let mut outgoing: HashMap<&str, Vec<usize>> = HashMap::new();
+        for (index, edge) in self.edges.iter().enumerate() {
+            outgoing.entry(&edge.from).or_default().push(index);
+        }
+        let mut redundant = vec![false; self.edges.len()];
+
+        // Edges are considered one at a time against those still in place, as in a cycle two edges
+        // can each be implied by a path through the other.
+        for index in 0..self.edges.len() {
+            let edge = &self.edges[index];
+            if edge.kind == EdgeKind::Vends {
+                continue;
+            }
+            let (from, to) = (edge.from.as_str(), edge.to.as_str());
+            let mut visited = HashSet::from([from]);
+            let mut stack = vec![from];
+            let mut implied = false;
+            while let Some(current) = stack.pop() {
+                for &other in outgoing.get(current).into_iter().flatten() {
+                    let next = &self.edges[other];
+                    if other == index || redundant[other] || next.kind == EdgeKind::Vends {
+                        continue;
+                    }
+                    if next.to == to {
+                        implied = true;
+                        break;
+                    }
+                    if visited.insert(&next.to) {
+                        stack.push(&next.to);
+                    }
+                }
+                if implied {
+                    break;
+                }
+            }
+            redundant[index] = implied;
+        }
+        for (edge, redundant) in self.edges.iter_mut().zip(redundant) {
+            edge.redundant = redundant;
+        }
+        if !keep_redundant {
+            self.edges.retain(|edge| !edge.redundant);
+        }
+    }
+
+    /// Finds the groups of nodes that depend on each other, using Tarjan's algorithm.
+    pub fn cycles(&self) -> Vec<Cycle> {
+        let missing = self.missing_nodes();
+        let ids: Vec<&str> = self
+            .nodes
+            .iter()
+            .map(|node| node.id.as_str())
+            .chain(missing)
+            .collect();
+        let index: HashMap<&str, usize> = ids.iter().enumerate().map(|(i, id)| (*id, i)).collect();
+        let mut outgoing = vec![Vec::new(); ids.len()];
+        for edge in &self.edges {
+            outgoing[index[edge.from.as_str()]].push(index[edge.to.as_str()]);
+        }
+
+        let mut order = vec![usize::MAX; ids.len()];
+        let mut low = vec![0; ids.len()];
+        let mut on_stack = vec![false; ids.len()];
+        let mut stack = Vec::new();
+        let mut components = Vec::new();
+        let mut counter = 0;
+        for root in 0..ids.len() {
+            if order[root] != usize::MAX {
+                continue;
+            }
+            // The nodes being visited, with the position of the next edge to follow.
+            let mut visiting = vec![(root, 0)];
+            order[root] = counter;
+            low[root] = counter;
+            counter += 1;
+            stack.push(root);
+            on_stack[root] = true;
+            while let Some(&mut (node, ref mut next)) = visiting.last_mut() {
+                if let Some(&to) = outgoing[node].get(*next) {
+                    *next += 1;
+                    if order[to] == usize::MAX {
+                        order[to] = counter;
+                        low[to] = counter;
+                        counter += 1;
+                        stack.push(to);
+                        on_stack[to] = true;
+                        visiting.push((to, 0));
+                    } else if on_stack[to] {
+                        low[node] = low[node].min(order[to]);
+                    }
+                    continue;
+                }
+                visiting.pop();
+                if let Some(&(parent, _)) = visiting.last() {
+                    low[parent] = low[parent].min(low[node]);
+                }
+                if low[node] == order[node] {
+                    let mut component = Vec::new();
+                    while let Some(member) = stack.pop() {
+                        on_stack[member] = false;
+                        component.push(member);
+                        if member == node {
+                            break;
+                        }
+                    }
+                    let looped = component.len() > 1 || outgoing[node].contains(&node);
+                    if looped {
+                        component.sort_unstable();
+                        components.push(component);
+                    }
+                }
+            }
+        }
+        components.sort_unstable();
+
+        components
+            .into_iter()
+            .map(|component| {
+                let members: HashSet<usize> = component.iter().copied().collect();
+                let start = component[0];
+                // Breadth-first search within the component for the shortest way back to `start`.
+                let mut previous: HashMap<usize, usize> = HashMap::new();
+                let mut queue = VecDeque::from([start]);
+                let mut last = start;
+                'search: while let Some(node) = queue.pop_front() {
+                    for &to in &outgoing[node] {
+                        if to == start {
+                            last = node;
+                            break 'search;
+                        }
+                        if members.contains(&to) && !previous.contains_key(&to) {
+                            previous.insert(to, node);
+                            queue.push_back(to);
+                        }
+                    }
+                }
+                let mut path = vec![start];
+                let mut node = last;
+                while node != start {
+                    path.push(node);
+                    node = previous[&node];
+                }
+                path.push(start);
+                path.reverse();
+                Cycle {
+                    nodes: component.iter().map(|&i| ids[i].to_string()).collect(),
+                    path: path.iter().map(|&i| ids[i].to_string()).collect(),
+                }
+            })
+            .collect()
+    }
+
+    /// Sets `in_cycle` on the edges between nodes that depend on each other.
+    pub fn mark_cycles(&mut self) {
+        let mut component: HashMap<String, usize> = HashMap::new();
+        for (index, cycle) in self.cycles().into_iter().enumerate() {
+            for id in cycle.nodes {
+                component.insert(id, index);
+            }
+        }
+        for edge in &mut self.edges {
+            edge.in_cycle = match (component.get(&edge.from), component.get(&edge.to)) {
+                (Some(from), Some(to)) => from == to,
+                _ => false,
+            };
+        }
+    }
+
+    /// Groups the targets into levels that can be built in order, each depending only on targets
+    /// in earlier levels, so the targets within a level can be built in parallel. Targets keep
+    /// the package's order within a level. `None` if targets depend on each other.
+    pub fn build_levels(&self) -> Option<Vec<Vec<&Node>>> {
+        let targets: Vec<&Node> = self
+            .nodes
+            .iter()
+            .filter(|node| matches!(node.kind, NodeKind::Target(_)))
+            .collect();
+        let index: HashMap<&str, usize> = targets
+            .iter()
+            .enumerate()
+            .map(|(i, node)| (node.id.as_str(), i))
+            .collect();
+        let mut dependents = vec![Vec::new(); targets.len()];
+        let mut remaining = vec![0; targets.len()];
+        for edge in self
+            .edges
+            .iter()
+            .filter(|edge| edge.kind == EdgeKind::Target)
+        {
+            if let (Some(&from), Some(&to)) =
+                (index.get(edge.from.as_str()), index.get(edge.to.as_str()))
+            {
+                dependents[to].push(from);
+                remaining[from] += 1;
+            }
+        }
+
+        let mut levels = Vec::new();
+        let mut level: Vec<usize> = (0..targets.len()).filter(|&i| remaining[i] == 0).collect();
+        let mut placed = 0;
+        while !level.is_empty() {
+            placed += level.len();
+            let mut next = Vec::new();
+            for &target in &level {
+                for &dependent in &dependents[target] {
+                    remaining[dependent] -= 1;
+                    if remaining[dependent] == 0 {
+                        next.push(dependent);
+                    }
+                }
+            }
+            next.sort_unstable();
+            levels.push(level.iter().map(|&i| targets[i]).collect());
+            level = next;
+        }
+        (placed == targets.len()).then_some(levels)
+    }
+
+    /// The paths from one node to another, as lists of node ids, shortest first. Paths may follow
+    /// any edge, including those into external products and from products to their targets, but
+    /// never visit a node twice. With a `limit`, only that many of the shortest paths are found.
+    pub fn paths(&self, from: &str, to: &str, limit: Option<usize>) -> Vec<Vec<String>> {
+        let mut outgoing: HashMap<&str, Vec<&str>> = HashMap::new();
+        let mut incoming: HashMap<&str, Vec<&str>> = HashMap::new();
+        for edge in &self.edges {
+            let next = outgoing.entry(&edge.from).or_default();
+            if !next.contains(&edge.to.as_str()) {
+                next.push(&edge.to);
+                incoming.entry(&edge.to).or_default().push(&edge.from);
+            }
+        }
+        // How far each node is from `to`, so the search never extends a path into a node `to`
+        // cannot be reached from, or past the length it is looking for.
+        let mut distances = HashMap::from([(to, 0)]);
+        let mut queue = VecDeque::from([to]);
+        while let Some(id) = queue.pop_front() {
+            let distance = distances[id] + 1;
+            for &previous in incoming.get(id).into_iter().flatten() {
+                distances.entry(previous).or_insert_with(|| {
+                    queue.push_back(previous);
+                    distance
+                });
+            }
+        }
+
+        let mut paths = Vec::new();
+        let Some(&shortest) = distances.get(from) else {
+            return paths;
+        };
+        let search = PathSearch {
+            outgoing: &outgoing,
+            distances: &distances,
+            to,
+        };
+        match limit {
+            // Look for paths of each length in turn, so the longer ones are never enumerated.
+            Some(limit) => {
+                for length in shortest..distances.len() {
+                    if paths.len() >= limit {
+                        break;
+                    }
+                    search.extend(&mut vec![from], Some(length), limit, &mut paths);
+                }
+            }
+            None => {
+                search.extend(&mut vec![from], None, usize::MAX, &mut paths);
+                paths.sort_by_key(Vec::len);
+            }
+        }
+        paths
+    }
+
+    /// Removes every node and edge that is not on one of the paths.
+    pub fn keep_paths(&mut self, paths: &[Vec<String>]) {
+        let nodes: HashSet<&str> = paths.iter().flatten().map(String::as_str).collect();
+        let edges: HashSet<(&str, &str)> = paths
+            .iter()
+            .flat_map(|path| path.windows(2))
+            .map(|pair| (pair[0].as_str(), pair[1].as_str()))
+            .collect();
+        self.edges
+            .retain(|edge| edges.contains(&(edge.from.as_str(), edge.to.as_str())));
+        let nodes: HashSet<String> = nodes.into_iter().map(str::to_string).collect();
+        self.retain(|id| nodes.contains(id));
+    }
+
+    /// Removes the nodes whose ids `keep` rejects, along with their edges and any external package
+    /// left without products.
+    fn retain(&mut self, keep: impl Fn(&str) -> bool) {
+        self.nodes.retain(|node| keep(&node.id));
+        self.edges.retain(|edge| keep(&edge.from) && keep(&edge.to));
+        let nodes = &self.nodes;
+        self.packages.retain(|package| {
+            nodes.iter().any(|node| {
+                matches!(&node.kind, NodeKind::ExternalProduct { package: Some(other) } if *other == package.identity)
+            })
+        });
+    }
+
+    /// The nodes of the package itself, i.e. its products and targets.
+    pub fn local_nodes(&self) -> impl Iterator<Item = &Node> {
+        self.nodes
+            .iter()
+            .filter(|node| !matches!(node.kind, NodeKind::ExternalProduct { .. }))
+    }
+
+    /// The package a node belongs to: this package for targets and products, else the identity of
+    /// the external package, if known.
+    pub fn package_of<'a>(&'a self, node: &'a Node) -> Option<&'a str> {
+        match &node.kind {
+            NodeKind::ExternalProduct { package } => package.as_deref(),
+            _ => Some(&self.name),
+        }
+    }
+
+    /// Ids that edges refer to but that have no node. `retain` drops the edges of every node it
+    /// removes, so these are only ever test targets left out by `skip_test_targets` that other
+    /// targets or products still depend on. Renderers still draw them, as formats such as GraphML
+    /// require every edge endpoint to exist.
+    pub fn missing_nodes(&self) -> BTreeSet<&str> {
+        let ids: BTreeSet<&str> = self.nodes.iter().map(|node| node.id.as_str()).collect();
+        self.edges
+            .iter()
+            .flat_map(|edge| [edge.from.as_str(), edge.to.as_str()])
+            .filter(|id| !ids.contains(id))
+            .collect()
+    }
+
+    /// The external products provided by `package`, or those whose package is unknown.
+    pub fn external_products<'a>(
+        &'a self,
+        package: Option<&'a str>,
+    ) -> impl Iterator<Item = &'a Node> {
+        self.nodes.iter().filter(move |node| match &node.kind {
+            NodeKind::ExternalProduct { package: other } => other.as_deref() == package,
+            _ => false,
+        })
+    }
+}
+
+/// Products are qualified by the package providing them, as two packages may vend products with the
+/// same name. Those from an unknown package are still namespaced, as a package commonly has a target
+/// named after the product it wraps.
+fn external_product_id(package: Option<&str>, product: &str) -> String {
+    match package {
+        Some(package) => format!("{}/{}", package, product),
+        None => format!("external:{}", product),
+    }
+}
+
+/// A depth-first search for the paths to `to`.
+struct PathSearch<'a> {
+    outgoing: &'a HashMap<&'a str, Vec<&'a str>>,
+    distances: &'a HashMap<&'a str, usize>,
+    to: &'a str,
+}
+
+impl<'a> PathSearch<'a> {
+    /// Adds the paths that continue `path` to `paths`, until there are `limit` of them. With a
+    /// `length`, only paths with exactly that many edges are added.
+    fn extend(
+        &self,
+        path: &mut Vec<&'a str>,
+        length: Option<usize>,
+        limit: usize,
+        paths: &mut Vec<Vec<String>>,
+    ) {
+        let last = path[path.len() - 1];
+        if last == self.to {
+            if length.is_some_and(|length| path.len() - 1 != length) {
+                return;
+            }
+            paths.push(path.iter().map(|id| id.to_string()).collect());
+            return;
+        }
+        for &next in self.outgoing.get(last).into_iter().flatten() {
+            if paths.len() >= limit {
+                return;
+            }
+            let Some(&distance) = self.distances.get(next) else {
+                continue;
+            };
+            if length.is_some_and(|length| path.len() + distance > length) || path.contains(&next) {
+                continue;
+            }
+            path.push(next);
+            self.extend(path, length, limit, paths);
+            path.pop();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::package::LibraryType;
+
+    /// A graph of library targets with the given dependencies between them.
+    fn graph(targets: &[&str], dependencies: &[(&str, &str)]) -> Graph {
+        Graph {
+            name: "Package".to_string(),
+            nodes: targets
+                .iter()
+                .map(|name| Node {
+                    id: name.to_string(),
+                    name: name.to_string(),
+                    kind: NodeKind::Target(TargetType::Library),
+                    path: None,
+                    source_count: None,
+                })
+                .collect(),
+            edges: dependencies
+                .iter()
+                .map(|(from, to)| edge(from, to, EdgeKind::Target))
+                .collect(),
+            packages: Vec::new(),
+        }
+    }
+
+    fn edge(from: &str, to: &str, kind: EdgeKind) -> Edge {
+        Edge {
+            from: from.to_string(),
+            to: to.to_string(),
+            kind,
+            condition: None,
+            redundant: false,
+            in_cycle: false,
+        }
+    }
+
+    fn ids(graph: &Graph) -> Vec<&str> {
+        graph.nodes.iter().map(|node| node.id.as_str()).collect()
+    }
+
+    #[test]
+    fn external_products_of_unknown_packages_do_not_collide_with_targets() {
+        let package: Package = serde_json::from_str(
+            r#"{"name": "Wrap", "products": [], "targets": [
+                {"name": "App", "type": "executable", "target_dependencies": ["Logging"]},
+                {"name": "Logging", "type": "library", "product_dependencies": ["Logging"]}
+            ]}"#,
+        )
+        .unwrap();
+        let graph = Graph::new(
+            &package,
+            &ManifestDependencies::default(),
+            &Resolved::default(),
+            &GraphOptions::default(),
+        );
+        assert_eq!(ids(&graph), ["App", "Logging", "external:Logging"]);
+        assert!(graph.cycles().is_empty());
+    }
+
+    #[test]
+    fn focus_leaves_out_other_dependents_of_dependencies() {
+        let mut graph = graph(
+            &["Tool", "Other", "CoreTests", "Core"],
+            &[("Tool", "Core"), ("Other", "Core"), ("CoreTests", "Core")],
+        );
+        graph.nodes.push(Node {
+            id: "product:Core".to_string(),
+            name: "Core".to_string(),
+            kind: NodeKind::Product(ProductType::Library(LibraryType::Automatic)),
+            path: None,
+            source_count: None,
+        });
+        graph
+            .edges
+            .push(edge("product:Core", "Core", EdgeKind::Vends));
+
+        graph.focus("Tool", None, Direction::Both);
+        assert_eq!(ids(&graph), ["Tool", "Core"]);
+    }
+
+    #[test]
+    fn focus_counts_depth_in_each_direction() {
+        let mut graph = graph(
+            &["App", "Tool", "Core", "Base"],
+            &[("App", "Tool"), ("Tool", "Core"), ("Core", "Base")],
+        );
+        graph.focus("Tool", Some(1), Direction::Both);
+        assert_eq!(ids(&graph), ["App", "Tool", "Core"]);
+    }
+
+    #[test]
+    fn a_target_depending_on_itself_is_a_cycle() {
+        let mut graph = graph(&["A", "B"], &[("A", "A"), ("A", "B")]);
+        let cycles = graph.cycles();
+        assert_eq!(cycles.len(), 1);
+        assert_eq!(cycles[0].nodes, ["A"]);
+        assert_eq!(cycles[0].path, ["A", "A"]);
+
+        graph.mark_cycles();
+        let in_cycle: Vec<bool> = graph.edges.iter().map(|edge| edge.in_cycle).collect();
+        assert_eq!(in_cycle, [true, false]);
+    }
+
+    #[test]
+    fn collapse_bridges_chains_of_removed_nodes() {
+        let mut graph = graph(
+            &["App", "Other", "Mid1", "Mid2", "Core", "Base"],
+            &[
+                ("App", "Mid1"),
+                ("App", "Core"),
+                ("Other", "Mid2"),
+                ("Mid1", "Mid2"),
+                ("Mid2", "Core"),
+                ("Mid2", "Base"),
+            ],
+        );
+        graph.remove_nodes(|_, name| name.starts_with("Mid"), true);
+        let edges: Vec<(&str, &str)> = graph
+            .edges
+            .iter()
+            .map(|edge| (edge.from.as_str(), edge.to.as_str()))
+            .collect();
+        assert_eq!(
+            edges,
+            [
+                ("App", "Core"),
+                ("App", "Base"),
+                ("Other", "Core"),
+                ("Other", "Base"),
+            ]
+        );
+        assert_eq!(ids(&graph), ["App", "Other", "Core", "Base"]);
+    }
+
+    #[test]
+    fn transitive_reduction_inside_a_cycle_keeps_reachability() {
+        let mut graph = graph(
+            &["A", "B", "C"],
+            &[("A", "B"), ("B", "A"), ("A", "C"), ("B", "C")],
+        );
+        graph.transitive_reduction(false);
+        let edges: Vec<(&str, &str)> = graph
+            .edges
+            .iter()
+            .map(|edge| (edge.from.as_str(), edge.to.as_str()))
+            .collect();
+        assert_eq!(edges, [("A", "B"), ("B", "A"), ("B", "C")]);
+    }
+
+    #[test]
+    fn build_levels_put_dependencies_first() {
+        let graph = graph(
+            &["App", "Tool", "Core", "Base"],
+            &[("App", "Tool"), ("App", "Core"), ("Tool", "Core")],
+        );
+        let levels: Vec<Vec<&str>> = graph
+            .build_levels()
+            .unwrap()
+            .iter()
+            .map(|level| level.iter().map(|node| node.id.as_str()).collect())
+            .collect();
+        assert_eq!(levels, [vec!["Core", "Base"], vec!["Tool"], vec!["App"]]);
+    }
+
+    #[test]
+    fn build_levels_fail_on_a_cycle() {
+        let graph = graph(
+            &["App", "Tool", "Core"],
+            &[("App", "Tool"), ("Tool", "Core"), ("Core", "Tool")],
+        );
+        assert!(graph.build_levels().is_none());
+    }
+
+    #[test]
+    fn paths_are_found_shortest_first() {
+        let graph = graph(
+            &["App", "Tool", "Core", "Base"],
+            &[
+                ("App", "Tool"),
+                ("App", "Core"),
+                ("Tool", "Core"),
+                ("Tool", "Base"),
+                ("Core", "Base"),
+                ("Base", "App"),
+            ],
+        );
+        let paths = graph.paths("App", "Base", None);
+        assert_eq!(
+            paths,
+            [
+                vec!["App", "Tool", "Base"],
+                vec!["App", "Core", "Base"],
+                vec!["App", "Tool", "Core", "Base"],
+            ]
+        );
+        assert_eq!(graph.paths("App", "Base", Some(2)), paths[..2]);
+        assert_eq!(
+            graph.paths("Core", "Tool", None),
+            [["Core", "Base", "App", "Tool"]]
+        );
+    }
+}
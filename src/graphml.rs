@@ -0,0 +1,78 @@
+use crate::graph::Graph;
+use crate::xml::{edge_attributes, escape, node_attributes, EDGE_ATTRIBUTES, NODE_ATTRIBUTES};
+use std::fmt::{self, Write};
+
+/// Renders the graph as GraphML, e.g. for yEd. Node and edge attributes are declared as GraphML
+/// keys.
+pub fn render(graph: &Graph) -> String {
+    let mut out = String::new();
+    write_graphml(&mut out, graph).expect("formatting into a String cannot fail");
+    out
+}
+
+fn write_graphml(out: &mut String, graph: &Graph) -> fmt::Result {
+    writeln!(out, r#"<?xml version="1.0" encoding="UTF-8"?>"#)?;
+    writeln!(
+        out,
+        r#"<graphml xmlns="http://graphml.graphdrawing.org/xmlns" xmlns:xsi="http://www.w3.org/2001/XMLSchema-instance" xsi:schemaLocation="http://graphml.graphdrawing.org/xmlns http://graphml.graphdrawing.org/xmlns/1.0/graphml.xsd">"#
+    )?;
+    writeln!(
+        out,
+        r#"  <key id="label" for="node" attr.name="label" attr.type="string"/>"#
+    )?;
+    for (name, attribute_type) in NODE_ATTRIBUTES {
+        writeln!(
+            out,
+            r#"  <key id="{0}" for="node" attr.name="{0}" attr.type="{1}"/>"#,
+            name, attribute_type
+        )?;
+    }
+    for (name, attribute_type) in EDGE_ATTRIBUTES {
+        writeln!(
+            out,
+            r#"  <key id="{0}" for="edge" attr.name="{0}" attr.type="{1}"/>"#,
+            name, attribute_type
+        )?;
+    }
+
+    writeln!(
+        out,
+        r#"  <graph id="{}" edgedefault="directed">"#,
+        escape(&graph.name)
+    )?;
+    for node in &graph.nodes {
+        writeln!(out, r#"    <node id="{}">"#, escape(&node.id))?;
+        writeln!(
+            out,
+            r#"      <data key="label">{}</data>"#,
+            escape(&node.name)
+        )?;
+        for (name, value) in node_attributes(graph, node) {
+            writeln!(
+                out,
+                r#"      <data key="{}">{}</data>"#,
+                name,
+                escape(&value)
+            )?;
+        }
+        writeln!(out, "    </node>")?;
+    }
+    for id in graph.missing_nodes() {
+        writeln!(out, r#"    <node id="{}"/>"#, escape(id))?;
+    }
+    for (index, edge) in graph.edges.iter().enumerate() {
+        writeln!(
+            out,
+            r#"    <edge id="e{}" source="{}" target="{}">"#,
+            index,
+            escape(&edge.from),
+            escape(&edge.to)
+        )?;
+        for (name, value) in edge_attributes(edge) {
+            writeln!(out, r#"      <data key="{}">{}</data>"#, name, value)?;
+        }
+        writeln!(out, "    </edge>")?;
+    }
+    writeln!(out, "  </graph>")?;
+    writeln!(out, "</graphml>")
+}
@@ -0,0 +1,18 @@
+use crate::graph::Graph;
+use crate::json;
+use crate::xml::escape;
+
+/// The viewer page, with `{{TITLE}}` and `{{GRAPH}}` placeholders for the package name and the
+/// graph's JSON.
+const VIEWER: &str = include_str!("viewer.html");
+
+/// Renders the graph as a single HTML page that lays it out and lets it be explored in a browser:
+/// pan, zoom, search, and highlighting a node's dependencies and dependents. The graph is embedded
+/// in the `json` format and nothing is loaded from the network, so the page works offline.
+pub fn render(graph: &Graph) -> String {
+    // `</` would end the script element the JSON is embedded in; `<\/` is the same JSON string.
+    let data = json::render(graph).replace("</", "<\\/");
+    VIEWER
+        .replace("{{TITLE}}", &escape(&graph.name))
+        .replace("{{GRAPH}}", data.trim_end())
+}
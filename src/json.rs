@@ -0,0 +1,133 @@
+use crate::graph::{Graph, Node};
+use crate::manifest::Condition;
+use serde::Serialize;
+
+/// Identifies the schema, so consumers can tell the output apart from other JSON.
+const SCHEMA: &str = "spm_to_graph.graph";
+
+/// Bumped whenever a field is removed or changes meaning. Adding fields does not bump it.
+const SCHEMA_VERSION: u32 = 1;
+
+#[derive(Serialize)]
+struct Document<'a> {
+    format: &'static str,
+    version: u32,
+    name: &'a str,
+    nodes: Vec<JsonNode<'a>>,
+    edges: Vec<JsonEdge<'a>>,
+    packages: Vec<JsonPackage<'a>>,
+}
+
+#[derive(Serialize)]
+struct JsonNode<'a> {
+    id: &'a str,
+    name: &'a str,
+    kind: &'static str,
+    package: Option<&'a str>,
+    attributes: Attributes<'a>,
+}
+
+#[derive(Default, Serialize)]
+struct Attributes<'a> {
+    #[serde(rename = "type", skip_serializing_if = "Option::is_none")]
+    node_type: Option<&'static str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    path: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    sources: Option<usize>,
+}
+
+#[derive(Serialize)]
+struct JsonEdge<'a> {
+    from: &'a str,
+    to: &'a str,
+    kind: &'static str,
+    conditions: Option<Conditions<'a>>,
+    /// Only present with `--transitive-reduction --show-redundant-edges`.
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    redundant: bool,
+    /// Whether the edge is part of a dependency cycle.
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    cycle: bool,
+}
+
+#[derive(Serialize)]
+struct Conditions<'a> {
+    platforms: &'a [String],
+    configuration: Option<&'a str>,
+}
+
+#[derive(Serialize)]
+struct JsonPackage<'a> {
+    identity: &'a str,
+    state: Option<&'a str>,
+}
+
+/// Renders the graph as JSON following a small, versioned schema, for scripts and other tools to
+/// consume instead of parsing one of the diagram formats.
+pub fn render(graph: &Graph) -> String {
+    let mut nodes: Vec<JsonNode> = graph
+        .nodes
+        .iter()
+        .map(|node| json_node(graph, node))
+        .collect();
+    // Every edge endpoint is listed as a node; those left out of the graph have kind `missing`.
+    nodes.extend(graph.missing_nodes().into_iter().map(|id| JsonNode {
+        id,
+        name: id,
+        kind: "missing",
+        package: None,
+        attributes: Attributes::default(),
+    }));
+    let document = Document {
+        format: SCHEMA,
+        version: SCHEMA_VERSION,
+        name: &graph.name,
+        nodes,
+        edges: graph
+            .edges
+            .iter()
+            .map(|edge| JsonEdge {
+                from: &edge.from,
+                to: &edge.to,
+                kind: edge.kind.name(),
+                conditions: edge.condition.as_ref().map(conditions),
+                redundant: edge.redundant,
+                cycle: edge.in_cycle,
+            })
+            .collect(),
+        packages: graph
+            .packages
+            .iter()
+            .map(|package| JsonPackage {
+                identity: &package.identity,
+                state: package.state.as_deref(),
+            })
+            .collect(),
+    };
+    let mut out =
+        serde_json::to_string_pretty(&document).expect("the graph always serializes to JSON");
+    out.push('\n');
+    out
+}
+
+fn json_node<'a>(graph: &'a Graph, node: &'a Node) -> JsonNode<'a> {
+    JsonNode {
+        id: &node.id,
+        name: &node.name,
+        kind: node.kind.name(),
+        package: graph.package_of(node),
+        attributes: Attributes {
+            node_type: node.kind.type_name(),
+            path: node.path.as_deref(),
+            sources: node.source_count,
+        },
+    }
+}
+
+fn conditions(condition: &Condition) -> Conditions<'_> {
+    Conditions {
+        platforms: &condition.platforms,
+        configuration: condition.configuration.as_deref(),
+    }
+}
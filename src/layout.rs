@@ -0,0 +1,387 @@
+use std::collections::HashMap;
+
+/// A layered (Sugiyama-style) layout: nodes are assigned to layers so that edges point down,
+/// edges spanning several layers are routed through placeholder slots in the layers between,
+/// nodes are ordered within their layer to reduce crossings, and finally given x coordinates.
+///
+/// Coordinates are in whatever unit the caller measures widths in, e.g. terminal columns.
+#[derive(Debug)]
+pub struct Layout {
+    /// The position of each node, in the order the nodes were given.
+    pub nodes: Vec<Position>,
+    /// The route of each edge, in the order the edges were given.
+    pub edges: Vec<Route>,
+    pub layer_count: usize,
+    pub width: usize,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct Position {
+    /// The left edge.
+    pub x: usize,
+    pub width: usize,
+    pub layer: usize,
+}
+
+impl Position {
+    pub fn center(&self) -> usize {
+        self.x + self.width / 2
+    }
+}
+
+#[derive(Debug)]
+pub struct Route {
+    /// The slots the edge passes through from its upper to its lower end, one per layer. Empty for
+    /// edges from a node to itself.
+    pub points: Vec<Position>,
+    /// Set when the edge points up, i.e. it was reversed to break a cycle.
+    pub reversed: bool,
+}
+
+/// A node or edge placeholder within a layer.
+struct Slot {
+    width: usize,
+    layer: usize,
+    /// The slots connected to this one in the layers above and below.
+    up: Vec<usize>,
+    down: Vec<usize>,
+}
+
+/// Lays out nodes of the given widths. Placeholders for long edges are `dummy_width` wide, and
+/// slots within a layer are at least `gap` apart.
+pub fn layout(
+    widths: &[usize],
+    edges: &[(usize, usize)],
+    dummy_width: usize,
+    gap: usize,
+) -> Layout {
+    let reversed = reversed_edges(widths.len(), edges);
+    let layers = assign_layers(widths.len(), edges, &reversed);
+
+    let mut slots: Vec<Slot> = widths
+        .iter()
+        .zip(&layers)
+        .map(|(&width, &layer)| Slot {
+            width,
+            layer,
+            up: Vec::new(),
+            down: Vec::new(),
+        })
+        .collect();
+    // The slots each edge passes through, from top to bottom.
+    let mut chains = Vec::with_capacity(edges.len());
+    for (index, &(from, to)) in edges.iter().enumerate() {
+        if from == to {
+            chains.push(Vec::new());
+            continue;
+        }
+        let (upper, lower) = if reversed[index] {
+            (to, from)
+        } else {
+            (from, to)
+        };
+        let mut chain = vec![upper];
+        for layer in layers[upper] + 1..layers[lower] {
+            slots.push(Slot {
+                width: dummy_width,
+                layer,
+                up: Vec::new(),
+                down: Vec::new(),
+            });
+            chain.push(slots.len() - 1);
+        }
+        chain.push(lower);
+        for pair in chain.windows(2) {
+            slots[pair[0]].down.push(pair[1]);
+            slots[pair[1]].up.push(pair[0]);
+        }
+        chains.push(chain);
+    }
+
+    let layer_count = layers.iter().max().map_or(0, |max| max + 1);
+    let order = order_layers(&slots, layer_count);
+    let x = assign_x(&slots, &order, gap);
+
+    let position = |slot: usize| Position {
+        x: x[slot],
+        width: slots[slot].width,
+        layer: slots[slot].layer,
+    };
+    Layout {
+        nodes: (0..widths.len()).map(position).collect(),
+        edges: chains
+            .iter()
+            .zip(reversed)
+            .map(|(chain, reversed)| Route {
+                points: chain.iter().map(|&slot| position(slot)).collect(),
+                reversed,
+            })
+            .collect(),
+        layer_count,
+        width: (0..slots.len())
+            .map(|slot| x[slot] + slots[slot].width)
+            .max()
+            .unwrap_or(0),
+    }
+}
+
+/// Finds a set of edges whose reversal makes the graph acyclic: the back edges of a depth-first
+/// search.
+fn reversed_edges(node_count: usize, edges: &[(usize, usize)]) -> Vec<bool> {
+    let mut outgoing = vec![Vec::new(); node_count];
+    for (index, &(from, to)) in edges.iter().enumerate() {
+        outgoing[from].push((index, to));
+    }
+    #[derive(Clone, Copy, PartialEq)]
+    enum State {
+        New,
+        Active,
+        Done,
+    }
+    let mut state = vec![State::New; node_count];
+    let mut reversed = vec![false; edges.len()];
+    for root in 0..node_count {
+        if state[root] != State::New {
+            continue;
+        }
+        state[root] = State::Active;
+        let mut stack = vec![(root, 0)];
+        while let Some((node, next)) = stack.last_mut() {
+            let node = *node;
+            if let Some(&(index, to)) = outgoing[node].get(*next) {
+                *next += 1;
+                match state[to] {
+                    State::New => {
+                        state[to] = State::Active;
+                        stack.push((to, 0));
+                    }
+                    State::Active if to != node => reversed[index] = true,
+                    _ => {}
+                }
+            } else {
+                state[node] = State::Done;
+                stack.pop();
+            }
+        }
+    }
+    reversed
+}
+
+/// Puts each node one layer below the lowest node pointing at it.
+fn assign_layers(node_count: usize, edges: &[(usize, usize)], reversed: &[bool]) -> Vec<usize> {
+    let mut outgoing = vec![Vec::new(); node_count];
+    let mut incoming = vec![0; node_count];
+    for (index, &(from, to)) in edges.iter().enumerate() {
+        if from == to {
+            continue;
+        }
+        let (from, to) = if reversed[index] {
+            (to, from)
+        } else {
+            (from, to)
+        };
+        outgoing[from].push(to);
+        incoming[to] += 1;
+    }
+    let mut layers = vec![0; node_count];
+    let mut ready: Vec<usize> = (0..node_count).filter(|&n| incoming[n] == 0).collect();
+    while let Some(node) = ready.pop() {
+        for &to in &outgoing[node] {
+            layers[to] = layers[to].max(layers[node] + 1);
+            incoming[to] -= 1;
+            if incoming[to] == 0 {
+                ready.push(to);
+            }
+        }
+    }
+    layers
+}
+
+/// Orders the slots of each layer by the average position of their neighbours, sweeping down and
+/// up a few times.
+fn order_layers(slots: &[Slot], layer_count: usize) -> Vec<Vec<usize>> {
+    let mut order = vec![Vec::new(); layer_count];
+    for (index, slot) in slots.iter().enumerate() {
+        order[slot.layer].push(index);
+    }
+    let mut rank: HashMap<usize, f64> = HashMap::new();
+    for layer in &order {
+        update_ranks(layer, &mut rank);
+    }
+    for _ in 0..4 {
+        for layer in order.iter_mut().skip(1) {
+            sort_by_barycenter(layer, &rank, |slot| &slots[slot].up);
+            update_ranks(layer, &mut rank);
+        }
+        for layer in order.iter_mut().rev().skip(1) {
+            sort_by_barycenter(layer, &rank, |slot| &slots[slot].down);
+            update_ranks(layer, &mut rank);
+        }
+    }
+    order
+}
+
+fn update_ranks(layer: &[usize], rank: &mut HashMap<usize, f64>) {
+    for (position, &slot) in layer.iter().enumerate() {
+        rank.insert(slot, position as f64);
+    }
+}
+
+fn sort_by_barycenter<'a>(
+    layer: &mut [usize],
+    rank: &HashMap<usize, f64>,
+    neighbours: impl Fn(usize) -> &'a Vec<usize>,
+) {
+    let key = |slot: usize| {
+        let neighbours = neighbours(slot);
+        if neighbours.is_empty() {
+            rank[&slot]
+        } else {
+            neighbours.iter().map(|n| rank[n]).sum::<f64>() / neighbours.len() as f64
+        }
+    };
+    let mut keyed: Vec<(f64, usize)> = layer.iter().map(|&slot| (key(slot), slot)).collect();
+    keyed.sort_by(|a, b| a.0.total_cmp(&b.0));
+    for (target, (_, slot)) in layer.iter_mut().zip(keyed) {
+        *target = slot;
+    }
+}
+
+/// Places the slots of each layer left to right, pulling each towards the centres of its
+/// neighbours while keeping them `gap` apart.
+fn assign_x(slots: &[Slot], order: &[Vec<usize>], gap: usize) -> Vec<usize> {
+    let gap = gap as i64;
+    let width = |slot: usize| slots[slot].width as i64;
+    let mut x = vec![0i64; slots.len()];
+    for layer in order {
+        let mut next = 0;
+        for &slot in layer {
+            x[slot] = next;
+            next += width(slot) + gap;
+        }
+    }
+
+    // Aligns a layer with the layer above it, or below it if `up` is false.
+    let place = |layer: &[usize], x: &mut Vec<i64>, up: bool| {
+        let desired: Vec<i64> = layer
+            .iter()
+            .map(|&slot| {
+                let neighbours = if up {
+                    &slots[slot].up
+                } else {
+                    &slots[slot].down
+                };
+                if neighbours.is_empty() {
+                    return x[slot];
+                }
+                let center = neighbours.iter().map(|&n| x[n] + width(n) / 2).sum::<i64>()
+                    / neighbours.len() as i64;
+                center - width(slot) / 2
+            })
+            .collect();
+        // Resolve overlaps once pushing right and once pushing left, and meet in the middle.
+        let mut right = desired.clone();
+        for i in 1..layer.len() {
+            right[i] = right[i].max(right[i - 1] + width(layer[i - 1]) + gap);
+        }
+        let mut left = desired;
+        for i in (0..layer.len().saturating_sub(1)).rev() {
+            left[i] = left[i].min(left[i + 1] - width(layer[i]) - gap);
+        }
+        let mut previous_end = None;
+        for (i, &slot) in layer.iter().enumerate() {
+            let mut position = (left[i] + right[i]) / 2;
+            if let Some(end) = previous_end {
+                position = position.max(end + gap);
+            }
+            x[slot] = position;
+            previous_end = Some(position + width(slot));
+        }
+    };
+    for _ in 0..8 {
+        for layer in order.iter().skip(1) {
+            place(layer, &mut x, true);
+        }
+        for layer in order.iter().rev().skip(1) {
+            place(layer, &mut x, false);
+        }
+    }
+
+    let min = x.iter().copied().min().unwrap_or(0);
+    x.into_iter().map(|x| (x - min) as usize).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A graph of 30 nodes of varying widths with edges in both directions, so some of them form
+    /// cycles and some span several layers.
+    fn graph() -> (Vec<usize>, Vec<(usize, usize)>) {
+        let widths = (0..30).map(|node| 3 + node * 7 % 11).collect();
+        let mut edges = Vec::new();
+        let mut seed: usize = 1;
+        for _ in 0..60 {
+            seed = (seed * 1103515245 + 12345) % (1 << 31);
+            let from = (seed >> 16) % 30;
+            seed = (seed * 1103515245 + 12345) % (1 << 31);
+            let to = (seed >> 16) % 30;
+            if from != to {
+                edges.push((from, to));
+            }
+        }
+        (widths, edges)
+    }
+
+    #[test]
+    fn edges_point_down_unless_reversed() {
+        let (widths, edges) = graph();
+        let layout = layout(&widths, &edges, 1, 2);
+        assert!(layout.edges.iter().any(|route| route.reversed));
+        for (&(from, to), route) in edges.iter().zip(&layout.edges) {
+            let (upper, lower) = if route.reversed {
+                (to, from)
+            } else {
+                (from, to)
+            };
+            assert!(layout.nodes[upper].layer < layout.nodes[lower].layer);
+            assert_eq!(
+                route.points.len(),
+                layout.nodes[lower].layer - layout.nodes[upper].layer + 1
+            );
+        }
+    }
+
+    #[test]
+    fn a_cycle_has_one_reversed_edge() {
+        let layout = layout(&[1, 1, 1, 1], &[(0, 1), (1, 2), (2, 3), (3, 0)], 1, 1);
+        let reversed = layout.edges.iter().filter(|route| route.reversed).count();
+        assert_eq!(reversed, 1);
+        assert_eq!(layout.layer_count, 4);
+    }
+
+    #[test]
+    fn slots_within_a_layer_do_not_overlap() {
+        let (widths, edges) = graph();
+        let gap = 2;
+        let layout = layout(&widths, &edges, 1, gap);
+        let mut layers = vec![Vec::new(); layout.layer_count];
+        let placeholders = layout
+            .edges
+            .iter()
+            .flat_map(|route| route.points.iter().skip(1).rev().skip(1));
+        for position in layout.nodes.iter().chain(placeholders) {
+            layers[position.layer].push(*position);
+        }
+        for layer in &mut layers {
+            layer.sort_by_key(|position| position.x);
+            for pair in layer.windows(2) {
+                assert!(pair[0].x + pair[0].width + gap <= pair[1].x, "{:?}", pair);
+            }
+        }
+        assert!(layout
+            .nodes
+            .iter()
+            .all(|node| node.x + node.width <= layout.width));
+    }
+}
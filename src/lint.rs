@@ -0,0 +1,275 @@
+use crate::graph::{EdgeKind, Graph, Node, NodeKind};
+use crate::package::TargetType;
+use crate::pattern::Pattern;
+use serde::de::{self, Deserializer};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::fmt;
+
+/// The rules file `lint` reads from the package directory unless `--rules` is given.
+pub const RULES_FILE: &str = "dependency-rules.json";
+
+/// Layering rules for the dependencies between targets, checked by the `lint` subcommand.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Rules {
+    pub rules: Vec<Rule>,
+}
+
+/// Restricts what the nodes matching `from` may depend on directly: nothing matching `forbid`,
+/// and when `allow` is given, only what matches it.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Rule {
+    /// Shown with each violation instead of a description of the rule.
+    name: Option<String>,
+    #[serde(deserialize_with = "selectors")]
+    from: Vec<Selector>,
+    #[serde(default, deserialize_with = "selectors")]
+    forbid: Vec<Selector>,
+    #[serde(default, deserialize_with = "optional_selectors")]
+    allow: Option<Vec<Selector>>,
+}
+
+/// Picks nodes either by target type, written `type:test`, or by a name [`Pattern`].
+#[derive(Debug)]
+struct Selector {
+    text: String,
+    matcher: Matcher,
+}
+
+#[derive(Debug)]
+enum Matcher {
+    Type(TargetType),
+    Name(Pattern),
+}
+
+const TARGET_TYPES: [TargetType; 8] = [
+    TargetType::Executable,
+    TargetType::Library,
+    TargetType::Macro,
+    TargetType::Test,
+    TargetType::Plugin,
+    TargetType::SystemTarget,
+    TargetType::Binary,
+    TargetType::Snippet,
+];
+
+impl Selector {
+    fn parse(text: &str) -> Result<Self, String> {
+        let matcher = match text.strip_prefix("type:") {
+            Some(name) => Matcher::Type(
+                TARGET_TYPES
+                    .into_iter()
+                    .find(|target_type| target_type.name() == name)
+                    .ok_or_else(|| format!("unknown target type `{}`", name))?,
+            ),
+            None => Matcher::Name(text.parse()?),
+        };
+        Ok(Selector {
+            text: text.to_string(),
+            matcher,
+        })
+    }
+
+    /// Whether the selector picks the node with this id, which may have been left out of the
+    /// graph and so only be known by its id.
+    fn matches(&self, id: &str, node: Option<&Node>) -> bool {
+        match &self.matcher {
+            Matcher::Type(target_type) => {
+                node.is_some_and(|node| node.kind == NodeKind::Target(*target_type))
+            }
+            Matcher::Name(pattern) => {
+                pattern.matches(id) || node.is_some_and(|node| pattern.matches(&node.name))
+            }
+        }
+    }
+}
+
+/// Deserializes a single selector or a list of them.
+fn selectors<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<Selector>, D::Error> {
+    struct Visitor;
+
+    impl<'de> de::Visitor<'de> for Visitor {
+        type Value = Vec<Selector>;
+
+        fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            f.write_str("a pattern or a list of patterns")
+        }
+
+        fn visit_str<E: de::Error>(self, value: &str) -> Result<Self::Value, E> {
+            Selector::parse(value)
+                .map(|selector| vec![selector])
+                .map_err(E::custom)
+        }
+
+        fn visit_seq<A: de::SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+            let mut selectors = Vec::new();
+            while let Some(text) = seq.next_element::<String>()? {
+                selectors.push(Selector::parse(&text).map_err(de::Error::custom)?);
+            }
+            Ok(selectors)
+        }
+    }
+
+    deserializer.deserialize_any(Visitor)
+}
+
+fn optional_selectors<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Option<Vec<Selector>>, D::Error> {
+    selectors(deserializer).map(Some)
+}
+
+/// A dependency that breaks a rule.
+pub struct Violation<'a> {
+    pub from: &'a str,
+    pub to: &'a str,
+    rule: &'a Rule,
+    /// Whether the dependency is outside the rule's `allow` list, rather than in `forbid`.
+    not_allowed: bool,
+}
+
+impl fmt::Display for Violation<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} -> {}: ", self.from, self.to)?;
+        if let Some(name) = &self.rule.name {
+            return f.write_str(name);
+        }
+        let (verb, selectors) = if self.not_allowed {
+            (
+                "may only depend on",
+                self.rule.allow.as_deref().unwrap_or_default(),
+            )
+        } else {
+            ("may not depend on", self.rule.forbid.as_slice())
+        };
+        write!(f, "{} {} {}", list(&self.rule.from), verb, list(selectors))
+    }
+}
+
+/// Lists selectors as written in the rules file, e.g. "`Core` or `Utilities`".
+fn list(selectors: &[Selector]) -> String {
+    selectors
+        .iter()
+        .map(|selector| format!("`{}`", selector.text))
+        .collect::<Vec<_>>()
+        .join(" or ")
+}
+
+impl Rules {
+    /// Checks every target and product dependency in the graph against the rules, in edge order.
+    /// The edges from products to the targets they vend are not dependencies and are ignored.
+    pub fn check<'a>(&'a self, graph: &'a Graph) -> Vec<Violation<'a>> {
+        let nodes: HashMap<&str, &Node> = graph
+            .nodes
+            .iter()
+            .map(|node| (node.id.as_str(), node))
+            .collect();
+        let mut violations = Vec::new();
+        for edge in graph
+            .edges
+            .iter()
+            .filter(|edge| edge.kind != EdgeKind::Vends)
+        {
+            let from = nodes.get(edge.from.as_str()).copied();
+            let to = nodes.get(edge.to.as_str()).copied();
+            let any = |selectors: &[Selector], id: &str, node: Option<&Node>| {
+                selectors.iter().any(|selector| selector.matches(id, node))
+            };
+            for rule in &self.rules {
+                if !any(&rule.from, &edge.from, from) {
+                    continue;
+                }
+                let forbidden = any(&rule.forbid, &edge.to, to);
+                let not_allowed = rule
+                    .allow
+                    .as_ref()
+                    .is_some_and(|allow| !any(allow, &edge.to, to));
+                if forbidden || not_allowed {
+                    violations.push(Violation {
+                        from: from.map_or(&edge.from, |node| &node.name),
+                        to: to.map_or(&edge.to, |node| &node.name),
+                        rule,
+                        not_allowed: !forbidden,
+                    });
+                }
+            }
+        }
+        violations
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::GraphOptions;
+    use crate::manifest::{ManifestDependencies, Resolved};
+    use crate::package::Package;
+
+    fn graph() -> Graph {
+        let package: Package = serde_json::from_str(
+            r#"{"name": "Layers", "products": [], "targets": [
+                {"name": "App", "type": "executable", "target_dependencies": ["Feature", "Core"]},
+                {"name": "Feature", "type": "library", "target_dependencies": ["Core"],
+                 "product_dependencies": ["Logging"]},
+                {"name": "Core", "type": "library"},
+                {"name": "CoreTests", "type": "test", "target_dependencies": ["Core"]},
+                {"name": "Helpers", "type": "library", "target_dependencies": ["CoreTests"]}
+            ]}"#,
+        )
+        .unwrap();
+        Graph::new(
+            &package,
+            &ManifestDependencies::default(),
+            &Resolved::default(),
+            &GraphOptions::default(),
+        )
+    }
+
+    fn rules(json: &str) -> serde_json::Result<Rules> {
+        serde_json::from_str(json)
+    }
+
+    #[test]
+    fn reports_forbidden_and_not_allowed_dependencies() {
+        let rules = rules(
+            r#"{"rules": [
+                {"name": "Libraries must not depend on tests", "from": "type:library",
+                 "forbid": "type:test"},
+                {"from": "App", "forbid": "Core"},
+                {"from": ["Feature", "Helpers"], "allow": "Core*"},
+                {"from": "Helpers", "forbid": "CoreTests", "allow": ["Core"]}
+            ]}"#,
+        )
+        .unwrap();
+        let graph = graph();
+        let violations: Vec<String> = rules
+            .check(&graph)
+            .iter()
+            .map(Violation::to_string)
+            .collect();
+        assert_eq!(
+            violations,
+            [
+                "App -> Core: `App` may not depend on `Core`",
+                "Feature -> Logging: `Feature` or `Helpers` may only depend on `Core*`",
+                "Helpers -> CoreTests: Libraries must not depend on tests",
+                "Helpers -> CoreTests: `Helpers` may not depend on `CoreTests`",
+            ]
+        );
+    }
+
+    #[test]
+    fn rejects_unknown_target_types_and_fields() {
+        let error = rules(r#"{"rules": [{"from": "type:widget", "forbid": "Core"}]}"#)
+            .unwrap_err()
+            .to_string();
+        assert!(error.contains("unknown target type `widget`"), "{}", error);
+
+        let error = rules(r#"{"rules": [{"from": "App", "forbids": "Core"}]}"#)
+            .unwrap_err()
+            .to_string();
+        assert!(error.contains("unknown field `forbids`"), "{}", error);
+    }
+}
@@ -1,46 +1,162 @@
+mod d2;
+mod dot;
+mod error;
+mod format;
+mod gexf;
+mod graph;
+mod graphml;
+mod html;
+mod json;
+mod layout;
+mod lint;
+mod manifest;
+mod mermaid;
+mod package;
+mod pattern;
+mod plantuml;
+mod stats;
+mod svg;
+mod terminal;
+mod xml;
+
 use clap::Parser;
-use serde::Deserialize;
-use std::io::Write;
-use std::path::PathBuf;
-use std::process::Command;
-use tabbycat::attributes::*;
-use tabbycat::{AttrList, Edge, GraphBuilder, GraphType, Identity, StmtList};
+use dot::DotOptions;
+use error::{Error, Result};
+use format::{Format, LayoutEngine};
+use graph::{Direction, Graph, GraphOptions};
+use lint::Rules;
+use manifest::{Manifest, ManifestDependencies, Resolved};
+use package::Package;
+use pattern::Pattern;
+use serde::de::DeserializeOwned;
+use stats::{Stats, StatsFormat};
+use std::fmt;
+use std::io::{ErrorKind, IsTerminal, Read, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Command, ExitCode, Stdio};
+use terminal::TerminalOptions;
 
-#[derive(Debug, Deserialize)]
-pub struct Package {
-    name: String,
-    targets: Vec<Target>,
-}
+#[derive(Parser)]
+#[command(
+    version,
+    about,
+    long_about = None,
+    args_conflicts_with_subcommands = true,
+    subcommand_negates_reqs = true
+)]
+struct Cli {
+    #[command(subcommand)]
+    action: Option<Action>,
 
-#[derive(Debug, Deserialize)]
-pub struct Target {
-    name: String,
-    #[serde(rename = "type")]
-    target_type: TargetType,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    product_dependencies: Option<Vec<String>>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    target_dependencies: Option<Vec<String>>,
-}
+    #[command(flatten)]
+    input: InputArgs,
+
+    #[command(flatten)]
+    output: OutputArgs,
+
+    #[clap(long)]
+    /// Leave out dependencies implied by other paths, e.g. A -> C when A -> B -> C exists
+    transitive_reduction: bool,
+
+    #[clap(long, requires = "transitive_reduction")]
+    /// Draw the dependencies left out by `--transitive-reduction` dashed instead of hiding them
+    show_redundant_edges: bool,
+
+    #[clap(long, value_name = "TARGET")]
+    /// Only graph the neighborhood of this target or product: its dependencies and dependents
+    focus: Option<String>,
+
+    #[clap(long, value_name = "N", requires = "focus")]
+    /// Only include nodes at most this many edges away from the focused one
+    depth: Option<usize>,
 
-#[derive(Debug, Deserialize, PartialEq)]
-#[serde(rename_all = "snake_case")]
-pub enum TargetType {
-    Executable,
-    Library,
-    Macro,
-    Test,
+    #[clap(long, requires = "focus")]
+    /// Only include what the focused node depends on
+    dependencies: bool,
+
+    #[clap(long, requires = "focus")]
+    /// Only include what depends on the focused node
+    dependents: bool,
 }
 
-// MARK: -
+// What to do instead of rendering the graph.
+#[derive(clap::Subcommand)]
+enum Action {
+    /// Report groups of targets and products that depend on each other, and fail if there are any
+    CheckCycles {
+        #[command(flatten)]
+        input: InputArgs,
+    },
+    /// Check the package's dependencies against layering rules, and fail if any are broken
+    Lint {
+        #[command(flatten)]
+        input: InputArgs,
 
-#[derive(Parser)]
-#[command(version, about, long_about = None)]
-struct Cli {
+        #[clap(long, value_name = "PATH", required_unless_present = "input")]
+        /// JSON file of rules, defaults to `dependency-rules.json` in the package directory
+        rules: Option<PathBuf>,
+    },
+    /// Print coupling metrics for each target, and totals for the package
+    Stats {
+        #[command(flatten)]
+        input: InputArgs,
+
+        #[clap(long, default_value = "table")]
+        /// Print a table, or JSON or CSV for other tools
+        format: StatsFormat,
+    },
+    /// Print the targets in an order they can be built in, one line of targets that can be built
+    /// in parallel at a time
+    Order {
+        #[command(flatten)]
+        input: InputArgs,
+
+        #[clap(long)]
+        /// Print one target per line instead of grouping them into levels
+        flat: bool,
+    },
+    /// Print the paths along which a target or product depends on another, or render them with
+    /// an output file or `--format`
+    Why {
+        /// The dependent target or product
+        from: String,
+
+        /// The target or product it depends on, possibly through others
+        to: String,
+
+        #[clap(long, value_name = "N", value_parser = clap::builder::RangedU64ValueParser::<usize>::new().range(1..))]
+        /// Only print the N shortest paths
+        shortest: Option<usize>,
+
+        #[command(flatten)]
+        input: InputArgs,
+
+        #[command(flatten)]
+        output: OutputArgs,
+    },
+}
+
+// Where the package comes from and what to include in its graph, shared by all subcommands. A
+// plain comment, as clap would otherwise take a doc comment as the program's description.
+#[derive(clap::Args)]
+struct InputArgs {
     /// Directory containing the Swift package
+    #[clap(required_unless_present = "from_json")]
     input: Option<PathBuf>,
-    /// Output file, defaults to package name with .dot extension
-    output: Option<PathBuf>,
+
+    #[clap(long, value_name = "PATH")]
+    /// Read `swift package describe --type json` output from a file (or `-` for stdin) instead of
+    /// running swift. When rendering, a lone positional argument is then the output file.
+    from_json: Option<PathBuf>,
+
+    #[clap(long, value_name = "PATH")]
+    /// Read `swift package dump-package` output from a file (or `-` for stdin), used to resolve
+    /// which package provides each product dependency. Only needed with `--from-json`.
+    dump_package_json: Option<PathBuf>,
+
+    #[clap(long, value_name = "PATH")]
+    /// Package.resolved file, defaults to the one in the package directory
+    resolved: Option<PathBuf>,
 
     #[clap(long)]
     /// Skip unit test targets
@@ -49,114 +165,490 @@ struct Cli {
     #[clap(long)]
     /// Skip external product dependencies
     skip_product_dependencies: bool,
+
+    #[clap(long)]
+    /// Skip the products this package vends
+    skip_products: bool,
+
+    #[clap(long, value_name = "PATTERN")]
+    /// Only include targets and products whose name matches one of these patterns: globs such as
+    /// `Core*`, or regular expressions prefixed with `re:`
+    include: Vec<Pattern>,
+
+    #[clap(long, value_name = "PATTERN")]
+    /// Leave out targets and products whose name matches one of these patterns, e.g. `*Tests`
+    exclude: Vec<Pattern>,
+
+    #[clap(long)]
+    /// Connect the dependents of nodes left out by `--include` and `--exclude` to their
+    /// dependencies, instead of dropping their edges
+    collapse: bool,
 }
 
-fn main() {
-    let cli = Cli::parse();
+// Where and how to render the graph. A plain comment for the same reason as `InputArgs`.
+#[derive(clap::Args)]
+struct OutputArgs {
+    /// Output file, defaults to package name with an extension matching the format. Use `-` to
+    /// write to stdout.
+    output: Option<PathBuf>,
+
+    #[clap(long, conflicts_with = "output")]
+    /// Write the graph to stdout instead of a file
+    stdout: bool,
 
+    #[clap(long)]
+    /// Output format, inferred from the output file's extension if not given. `dot` writes DOT
+    /// source; other Graphviz output formats (png, pdf, jpg, gif, xdot, plain, canon, ...) are
+    /// rendered with `dot`. `svg` is laid out without Graphviz unless `--graphviz` is given.
+    format: Option<Format>,
+
+    #[clap(long)]
+    /// Render SVG with Graphviz instead of the built-in layout
+    graphviz: bool,
+
+    #[clap(long, value_name = "ENGINE")]
+    /// Graphviz layout engine used to render the graph. Implies `--graphviz` for SVG
+    layout_engine: Option<LayoutEngine>,
+
+    #[clap(long)]
+    /// Group the package's targets, and each external package's products, into labelled clusters
+    clusters: bool,
+}
+
+fn swift_package(input: &Path, args: &[&str]) -> Result<Vec<u8>> {
     let output = Command::new("swift")
-        .args(["package", "describe", "--type", "json"])
-        .current_dir(cli.input.unwrap())
+        .arg("package")
+        .args(args)
+        .current_dir(input)
         .output()
-        .expect("failed to execute process");
+        .map_err(|error| match error.kind() {
+            ErrorKind::NotFound => Error::SwiftNotFound,
+            _ => Error::Swift(error),
+        })?;
+    if !output.status.success() {
+        return Err(Error::SwiftFailed {
+            command: format!("swift package {}", args.join(" ")),
+            status: output.status,
+            stderr: String::from_utf8_lossy(&output.stderr).into_owned(),
+        });
+    }
+    Ok(output.stdout)
+}
 
-    let package: Package = serde_json::from_slice(&output.stdout).unwrap();
+fn read_json(path: &Path) -> Result<Vec<u8>> {
+    let result = if path.as_os_str() == "-" {
+        let mut bytes = Vec::new();
+        std::io::stdin().read_to_end(&mut bytes).map(|_| bytes)
+    } else {
+        std::fs::read(path)
+    };
+    result.map_err(|source| Error::ReadInput {
+        path: path.to_path_buf(),
+        source,
+    })
+}
 
-    let mut statements = StmtList::new();
+fn parse_json<T: DeserializeOwned>(bytes: &[u8], what: &str) -> Result<T> {
+    serde_json::from_slice(bytes).map_err(|source| Error::InvalidJson {
+        what: what.to_string(),
+        source,
+    })
+}
 
-    for target in package.targets {
-        if cli.skip_test_targets && target.target_type == TargetType::Test {
-            continue;
-        }
+/// Pipes the DOT source through Graphviz to render it in the given format.
+fn render_with_dot(
+    graph_bytes: &[u8],
+    format: &str,
+    layout_engine: Option<LayoutEngine>,
+    destination: &Destination,
+) -> Result<()> {
+    let mut dot = Command::new("dot");
+    if let Some(layout_engine) = layout_engine {
+        dot.arg(format!("-K{}", layout_engine));
+    }
+    dot.arg(format!("-T{}", format));
+    // Without `-o`, dot writes to our stdout.
+    if let Destination::File(path) = destination {
+        dot.arg("-o").arg(path);
+    }
+    let mut dot = dot
+        .stdin(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|error| match error.kind() {
+            ErrorKind::NotFound => Error::GraphvizNotFound,
+            _ => Error::Graphviz(error),
+        })?;
 
-        statements = statements.add_node(
-            Identity::id(&target.name).unwrap(),
-            None,
-            Some(
-                AttrList::new()
-                    .add_pair(color(Color::Black))
-                    .add_pair(shape(Shape::Box)),
-            ),
-        );
+    // Close stdin once written so dot sees the end of the graph. A failed write usually means dot
+    // exited early, in which case its own error is more useful than the broken pipe.
+    let mut stdin = dot.stdin.take().expect("stdin is piped");
+    let written = stdin.write_all(graph_bytes);
+    drop(stdin);
 
-        for target_dependency in target.target_dependencies.unwrap_or_default() {
-            statements = statements.add_node(
-                Identity::id(&target_dependency).unwrap(),
-                None,
-                Some(
-                    AttrList::new()
-                        .add_pair(color(Color::Black))
-                        .add_pair(shape(Shape::Box)),
-                ),
-            );
-
-            statements = statements.add_edge(
-                Edge::head_node(Identity::id(&target.name).unwrap(), None)
-                    .arrow_to_node(Identity::id(&target_dependency).unwrap(), None),
-            );
-        }
-        if !cli.skip_product_dependencies {
-            for product_dependency in target.product_dependencies.unwrap_or_default() {
-                statements = statements.add_node(
-                    Identity::id(&product_dependency).unwrap(),
-                    None,
-                    Some(
-                        AttrList::new()
-                            .add_pair(color(Color::Blue))
-                            .add_pair(shape(Shape::Box)),
-                    ),
-                );
+    let output = dot.wait_with_output().map_err(Error::Graphviz)?;
+    if !output.status.success() {
+        return Err(Error::GraphvizFailed {
+            status: output.status,
+            stderr: String::from_utf8_lossy(&output.stderr).into_owned(),
+        });
+    }
+    written.map_err(Error::Graphviz)?;
+    // Warnings are not fatal, but should not be lost either.
+    std::io::stderr()
+        .write_all(&output.stderr)
+        .map_err(Error::Graphviz)?;
+    Ok(())
+}
 
-                statements = statements.add_edge(
-                    Edge::head_node(Identity::id(&target.name).unwrap(), None)
-                        .arrow_to_node(Identity::id(&product_dependency).unwrap(), None),
-                );
+/// Where the graph is written.
+enum Destination {
+    File(PathBuf),
+    Stdout,
+}
+
+impl Destination {
+    fn write(&self, bytes: &[u8]) -> Result<()> {
+        let result = match self {
+            Destination::File(path) => std::fs::write(path, bytes),
+            Destination::Stdout => {
+                let mut stdout = std::io::stdout().lock();
+                match stdout.write_all(bytes).and_then(|_| stdout.flush()) {
+                    // The reader went away, e.g. `| head`; nobody is left to tell.
+                    Err(error) if error.kind() == ErrorKind::BrokenPipe => Ok(()),
+                    result => result,
+                }
             }
+        };
+        result.map_err(|source| Error::WriteOutput {
+            destination: self.to_string(),
+            source,
+        })
+    }
+}
+
+impl fmt::Display for Destination {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Destination::File(path) => write!(f, "{}", path.display()),
+            Destination::Stdout => write!(f, "stdout"),
         }
     }
-    let graph = GraphBuilder::default()
-        .graph_type(GraphType::DiGraph)
-        .strict(false)
-        .id(Identity::id(&package.name).unwrap())
-        .stmts(statements)
-        .build()
-        .unwrap();
-
-    let graph_string = graph.to_string();
-    let graph_bytes = graph_string.as_bytes();
-
-    let output_path = cli
-        .output
-        .unwrap_or_else(|| PathBuf::from(format!("{}.dot", &package.name)));
-    let output_extension = output_path
-        .extension()
-        .and_then(|ext| ext.to_str())
-        .unwrap_or("dot");
-
-    match output_extension {
-        "dot" => {
-            // write graph string to file
-            let mut file = std::fs::File::create(&output_path).unwrap();
-            file.write_all(graph_bytes).unwrap();
+}
+
+fn main() -> ExitCode {
+    match run(Cli::parse()) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(error) => {
+            eprintln!("error: {}", error);
+            ExitCode::from(error.exit_code())
         }
-        "svg" => {
-            let mut dot = Command::new("dot")
-                .args(["-Tsvg", "-o", output_path.to_str().unwrap()])
-                .stdin(std::process::Stdio::piped())
-                .spawn()
-                .unwrap();
-            dot.stdin.as_mut().unwrap().write_all(graph_bytes).unwrap();
+    }
+}
+
+fn run(mut cli: Cli) -> Result<()> {
+    match cli.action.take() {
+        Some(Action::CheckCycles { input }) => check_cycles(&input),
+        Some(Action::Lint { input, rules }) => lint(&input, rules),
+        Some(Action::Order { input, flat }) => order(&input, flat),
+        Some(Action::Why {
+            from,
+            to,
+            shortest,
+            mut input,
+            mut output,
+        }) => {
+            output_from_input(&mut input, &mut output);
+            why(&input, output, &from, &to, shortest)
         }
-        "png" => {
-            let mut dot = Command::new("dot")
-                .args(["-Tpng", "-o", output_path.to_str().unwrap()])
-                .stdin(std::process::Stdio::piped())
-                .spawn()
-                .unwrap();
-            dot.stdin.as_mut().unwrap().write_all(graph_bytes).unwrap();
+        Some(Action::Stats { input, format }) => {
+            let graph = load_graph(&input)?;
+            Destination::Stdout.write(Stats::new(&graph).render(format).as_bytes())
         }
-        _ => {
-            println!("Unknown output extension");
+        None => render(cli),
+    }
+}
+
+/// Reads the package and builds the graph described by the input arguments.
+fn load_graph(args: &InputArgs) -> Result<Graph> {
+    let (json, manifest_json) = match (&args.from_json, &args.input) {
+        (Some(path), _) => (
+            read_json(path)?,
+            args.dump_package_json
+                .as_deref()
+                .map(read_json)
+                .transpose()?,
+        ),
+        (None, Some(input)) => {
+            if !input.is_dir() {
+                return Err(Error::PackageNotFound(input.clone()));
+            }
+            (
+                swift_package(input, &["describe", "--type", "json"])?,
+                Some(swift_package(input, &["dump-package"])?),
+            )
+        }
+        (None, None) => unreachable!("clap requires input without --from-json"),
+    };
+    // A package directory given alongside `--from-json` still provides its `Package.resolved`.
+    let resolved_path = args.resolved.clone().or_else(|| {
+        args.input
+            .as_ref()
+            .filter(|input| input.is_dir())
+            .map(|input| input.join("Package.resolved"))
+            .filter(|path| path.exists())
+    });
+
+    let package: Package = parse_json(&json, "package description")?;
+    let resolved = match resolved_path {
+        Some(path) => {
+            Resolved::from_slice(&read_json(&path)?).map_err(|source| Error::InvalidJson {
+                what: path.display().to_string(),
+                source,
+            })?
+        }
+        None => Resolved::default(),
+    };
+    // The manifest only tells which package provides each product, so a manifest this version
+    // does not understand leaves products unqualified rather than failing the whole run.
+    let manifest_dependencies =
+        match manifest_json.map(|json| parse_json::<Manifest>(&json, "dump-package output")) {
+            Some(Ok(manifest)) => ManifestDependencies::new(&manifest, &resolved),
+            Some(Err(error)) => {
+                eprintln!(
+                    "warning: {}; product dependencies are not grouped by package",
+                    error
+                );
+                ManifestDependencies::default()
+            }
+            None => ManifestDependencies::default(),
+        };
+
+    let mut graph = Graph::new(
+        &package,
+        &manifest_dependencies,
+        &resolved,
+        &GraphOptions {
+            skip_test_targets: args.skip_test_targets,
+            skip_product_dependencies: args.skip_product_dependencies,
+            skip_products: args.skip_products,
+        },
+    );
+
+    if !args.include.is_empty() || !args.exclude.is_empty() {
+        let matches = |patterns: &[Pattern], id: &str, name: &str| {
+            patterns
+                .iter()
+                .any(|pattern| pattern.matches(name) || pattern.matches(id))
+        };
+        graph.remove_nodes(
+            |id, name| {
+                (!args.include.is_empty() && !matches(&args.include, id, name))
+                    || matches(&args.exclude, id, name)
+            },
+            args.collapse,
+        );
+    }
+    Ok(graph)
+}
+
+/// Prints each group of nodes that depend on each other with one of the cycles through it.
+fn check_cycles(args: &InputArgs) -> Result<()> {
+    let cycles = load_graph(args)?.cycles();
+    let mut report = String::new();
+    for cycle in &cycles {
+        report.push_str(&format!(
+            "cycle between {} nodes: {}\n  {}\n",
+            cycle.nodes.len(),
+            cycle.nodes.join(", "),
+            cycle.path.join(" -> ")
+        ));
+    }
+    if cycles.is_empty() {
+        report.push_str("no dependency cycles\n");
+    }
+    Destination::Stdout.write(report.as_bytes())?;
+    match cycles.len() {
+        0 => Ok(()),
+        count => Err(Error::Cycles(count)),
+    }
+}
+
+/// Prints each dependency that breaks one of the rules.
+fn lint(args: &InputArgs, rules: Option<PathBuf>) -> Result<()> {
+    let path = match (rules, &args.input) {
+        (Some(path), _) => path,
+        (None, Some(input)) => input.join(lint::RULES_FILE),
+        (None, None) => unreachable!("clap requires --rules without a package directory"),
+    };
+    let rules: Rules = parse_json(&read_json(&path)?, &path.display().to_string())?;
+    let graph = load_graph(args)?;
+    let violations = rules.check(&graph);
+    let mut report = String::new();
+    for violation in &violations {
+        report.push_str(&format!("{}\n", violation));
+    }
+    if violations.is_empty() {
+        report.push_str("no rule violations\n");
+    }
+    Destination::Stdout.write(report.as_bytes())?;
+    match violations.len() {
+        0 => Ok(()),
+        count => Err(Error::RuleViolations(count)),
+    }
+}
+
+/// Prints the targets in build order, each level on a line of its own unless `flat`.
+fn order(args: &InputArgs, flat: bool) -> Result<()> {
+    let graph = load_graph(args)?;
+    let levels = graph
+        .build_levels()
+        .ok_or_else(|| Error::Cycles(graph.cycles().len()))?;
+    let separator = if flat { "\n" } else { " " };
+    let mut report = String::new();
+    for level in levels {
+        let names: Vec<&str> = level.iter().map(|node| node.name.as_str()).collect();
+        report.push_str(&names.join(separator));
+        report.push('\n');
+    }
+    Destination::Stdout.write(report.as_bytes())
+}
+
+/// With no package directory to operate on, a lone positional argument is the output.
+fn output_from_input(input: &mut InputArgs, output: &mut OutputArgs) {
+    if input.from_json.is_some() && output.output.is_none() {
+        output.output = input.input.take();
+    }
+}
+
+/// Prints the paths from one node to another, or renders the graph of just those paths if any
+/// output was asked for.
+fn why(
+    args: &InputArgs,
+    output: OutputArgs,
+    from: &str,
+    to: &str,
+    shortest: Option<usize>,
+) -> Result<()> {
+    let mut graph = load_graph(args)?;
+    let [from, to] = [from, to].map(|name| {
+        graph
+            .find(name)
+            .map(|node| node.id.clone())
+            .ok_or_else(|| Error::UnknownNode(name.to_string()))
+    });
+    let (from, to) = (from?, to?);
+    let paths = graph.paths(&from, &to, shortest);
+
+    if output.output.is_some() || output.stdout || output.format.is_some() {
+        graph.keep_paths(&paths);
+        graph.mark_cycles();
+        return write_graph(&graph, output);
+    }
+    let name = |id: &str| {
+        graph
+            .find(id)
+            .map_or(id.to_string(), |node| node.name.clone())
+    };
+    let mut report = String::new();
+    for path in &paths {
+        let names: Vec<String> = path.iter().map(|id| name(id)).collect();
+        report.push_str(&names.join(" -> "));
+        report.push('\n');
+    }
+    if paths.is_empty() {
+        report.push_str(&format!(
+            "{} does not depend on {}\n",
+            name(&from),
+            name(&to)
+        ));
+    }
+    Destination::Stdout.write(report.as_bytes())
+}
+
+fn render(mut cli: Cli) -> Result<()> {
+    output_from_input(&mut cli.input, &mut cli.output);
+    let mut graph = load_graph(&cli.input)?;
+
+    if let Some(name) = &cli.focus {
+        let id = graph
+            .find(name)
+            .ok_or_else(|| Error::UnknownNode(name.clone()))?
+            .id
+            .clone();
+        let direction = match (cli.dependencies, cli.dependents) {
+            (true, false) => Direction::Dependencies,
+            (false, true) => Direction::Dependents,
+            _ => Direction::Both,
+        };
+        graph.focus(&id, cli.depth, direction);
+    }
+    if cli.transitive_reduction {
+        graph.transitive_reduction(cli.show_redundant_edges);
+    }
+    graph.mark_cycles();
+    write_graph(&graph, cli.output)
+}
+
+/// Writes the graph in the requested format to the requested destination.
+fn write_graph(graph: &Graph, output: OutputArgs) -> Result<()> {
+    let format = match (output.format, &output.output) {
+        (Some(format), _) => format,
+        (None, Some(output)) => match output.extension().and_then(|ext| ext.to_str()) {
+            Some(extension) => Format::from_extension(extension)
+                .ok_or_else(|| Error::UnknownFormat(extension.to_string()))?,
+            None => Format::Dot,
+        },
+        (None, None) => Format::Dot,
+    };
+    let destination = match output.output {
+        _ if output.stdout => Destination::Stdout,
+        Some(path) if path.as_os_str() == "-" => Destination::Stdout,
+        Some(path) => Destination::File(path),
+        // Drawings for the terminal are meant to be looked at right away.
+        None if matches!(format, Format::Terminal | Format::Ascii) => Destination::Stdout,
+        None => Destination::File(PathBuf::from(format!(
+            "{}.{}",
+            graph.name,
+            format.extension()
+        ))),
+    };
+
+    let dot_options = DotOptions {
+        clusters: output.clusters,
+        layout_engine: output.layout_engine,
+    };
+    match &format {
+        Format::Dot => destination.write(dot::render(graph, &dot_options)?.as_bytes())?,
+        Format::Graphviz(name) => render_with_dot(
+            dot::render(graph, &dot_options)?.as_bytes(),
+            name,
+            output.layout_engine,
+            &destination,
+        )?,
+        Format::Mermaid => destination.write(mermaid::render(graph, output.clusters).as_bytes())?,
+        Format::PlantUml => destination.write(plantuml::render(graph).as_bytes())?,
+        Format::D2 => destination.write(d2::render(graph, output.clusters).as_bytes())?,
+        Format::GraphMl => destination.write(graphml::render(graph).as_bytes())?,
+        Format::Gexf => destination.write(gexf::render(graph).as_bytes())?,
+        Format::Json => destination.write(json::render(graph).as_bytes())?,
+        Format::Html => destination.write(html::render(graph).as_bytes())?,
+        Format::Svg if output.graphviz || output.layout_engine.is_some() => render_with_dot(
+            dot::render(graph, &dot_options)?.as_bytes(),
+            "svg",
+            output.layout_engine,
+            &destination,
+        )?,
+        Format::Svg => destination.write(svg::render(graph).as_bytes())?,
+        Format::Terminal | Format::Ascii => {
+            let options = TerminalOptions {
+                ascii: format == Format::Ascii,
+                color: matches!(destination, Destination::Stdout)
+                    && std::io::stdout().is_terminal()
+                    && std::env::var_os("NO_COLOR").is_none(),
+            };
+            destination.write(terminal::render(graph, &options).as_bytes())?
         }
     }
+    Ok(())
 }
@@ -0,0 +1,334 @@
+use serde::Deserialize;
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap};
+
+/// The subset of `swift package dump-package` output needed to tell which package vends each
+/// product dependency. `swift package describe` only reports product names.
+#[derive(Debug, Deserialize)]
+pub struct Manifest {
+    /// Each dependency is a single-key object keyed by its kind (`sourceControl`, `fileSystem`,
+    /// `registry`) wrapping a one-element array.
+    #[serde(default)]
+    dependencies: Vec<BTreeMap<String, Vec<ManifestDependency>>>,
+    targets: Vec<ManifestTarget>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ManifestDependency {
+    identity: String,
+    name_for_target_dependency_resolution_only: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ManifestTarget {
+    name: String,
+    #[serde(default)]
+    dependencies: Vec<TargetDependency>,
+}
+
+/// Target dependencies are encoded as positional arrays, e.g.
+/// `{"product": ["ArgumentParser", "swift-argument-parser", null, null]}`. The number of trailing
+/// elements varies between SwiftPM releases, so only the leading ones are interpreted.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum TargetDependency {
+    Target(Vec<Value>),
+    Product(Vec<Value>),
+    ByName(Vec<Value>),
+}
+
+impl TargetDependency {
+    fn string_at(values: &[Value], index: usize) -> Option<&str> {
+        values.get(index).and_then(Value::as_str)
+    }
+}
+
+impl Manifest {
+    /// Returns the identity of the dependency a manifest refers to as `name`, which is either its
+    /// identity or, for older manifests, the name of the package.
+    fn dependency_identity(&self, name: &str) -> Option<&str> {
+        self.dependencies
+            .iter()
+            .flat_map(|dependency| dependency.values().flatten())
+            .find(|dependency| {
+                dependency.identity.eq_ignore_ascii_case(name)
+                    || dependency
+                        .name_for_target_dependency_resolution_only
+                        .as_deref()
+                        == Some(name)
+            })
+            .map(|dependency| dependency.identity.as_str())
+    }
+}
+
+/// The pins recorded in `Package.resolved`, normalized across its file format versions.
+#[derive(Debug, Default)]
+pub struct Resolved {
+    pub pins: Vec<Pin>,
+}
+
+#[derive(Debug)]
+pub struct Pin {
+    pub identity: String,
+    /// The package name, only recorded by version 1 of the format.
+    pub name: Option<String>,
+    pub version: Option<String>,
+    pub branch: Option<String>,
+    pub revision: Option<String>,
+}
+
+impl Pin {
+    /// A short description of what the package is pinned to: its version, else its branch, else
+    /// an abbreviated revision.
+    pub fn state(&self) -> Option<String> {
+        self.version
+            .clone()
+            .or_else(|| self.branch.clone())
+            .or_else(|| {
+                self.revision
+                    .as_ref()
+                    .map(|revision| revision.chars().take(7).collect())
+            })
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum ResolvedFile {
+    V2 { pins: Vec<PinV2> },
+    V1 { object: ResolvedObjectV1 },
+}
+
+#[derive(Debug, Deserialize)]
+struct PinV2 {
+    identity: String,
+    #[serde(default)]
+    state: PinState,
+}
+
+#[derive(Debug, Deserialize)]
+struct ResolvedObjectV1 {
+    pins: Vec<PinV1>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PinV1 {
+    package: String,
+    #[serde(rename = "repositoryURL")]
+    repository_url: String,
+    #[serde(default)]
+    state: PinState,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct PinState {
+    version: Option<String>,
+    branch: Option<String>,
+    revision: Option<String>,
+}
+
+impl Resolved {
+    pub fn from_slice(bytes: &[u8]) -> serde_json::Result<Self> {
+        let pins = match serde_json::from_slice(bytes)? {
+            ResolvedFile::V2 { pins } => pins
+                .into_iter()
+                .map(|pin| Pin {
+                    identity: pin.identity,
+                    name: None,
+                    version: pin.state.version,
+                    branch: pin.state.branch,
+                    revision: pin.state.revision,
+                })
+                .collect(),
+            ResolvedFile::V1 { object } => object
+                .pins
+                .into_iter()
+                .map(|pin| Pin {
+                    identity: identity_from_location(&pin.repository_url),
+                    name: Some(pin.package),
+                    version: pin.state.version,
+                    branch: pin.state.branch,
+                    revision: pin.state.revision,
+                })
+                .collect(),
+        };
+        Ok(Resolved { pins })
+    }
+
+    /// Finds the pin for a package by identity or, for version 1 files, by name.
+    pub fn pin(&self, name: &str) -> Option<&Pin> {
+        self.pins.iter().find(|pin| {
+            pin.identity.eq_ignore_ascii_case(name) || pin.name.as_deref() == Some(name)
+        })
+    }
+}
+
+/// SwiftPM derives a package's identity from the last path component of its location.
+fn identity_from_location(location: &str) -> String {
+    let last = location
+        .trim_end_matches('/')
+        .rsplit('/')
+        .next()
+        .unwrap_or(location);
+    last.strip_suffix(".git").unwrap_or(last).to_lowercase()
+}
+
+/// The platforms and build configuration a target dependency is restricted to.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Condition {
+    pub platforms: Vec<String>,
+    pub configuration: Option<String>,
+}
+
+impl Condition {
+    /// Parses a condition such as `{"platformNames": ["linux"], "config": null}`. Returns `None` for
+    /// any other value, e.g. the module aliases that precede a product's condition.
+    fn from_value(value: &Value) -> Option<Self> {
+        let object = value.as_object()?;
+        if !object.contains_key("platformNames") && !object.contains_key("config") {
+            return None;
+        }
+        let platforms = object
+            .get("platformNames")
+            .and_then(Value::as_array)
+            .map(|names| {
+                names
+                    .iter()
+                    .filter_map(Value::as_str)
+                    .map(str::to_string)
+                    .collect()
+            })
+            .unwrap_or_default();
+        let configuration = match object.get("config") {
+            Some(Value::String(config)) => Some(config.clone()),
+            Some(Value::Object(config)) => config
+                .get("config")
+                .and_then(Value::as_str)
+                .map(str::to_string),
+            _ => None,
+        };
+        Some(Condition {
+            platforms,
+            configuration,
+        })
+    }
+}
+
+/// What the manifest adds to `swift package describe` about each target's dependencies: the
+/// identity of the package providing each product, and the condition each dependency applies
+/// under. Both are keyed by target and dependency name.
+#[derive(Debug, Default)]
+pub struct ManifestDependencies {
+    packages: HashMap<(String, String), String>,
+    conditions: HashMap<(String, String), Condition>,
+}
+
+impl ManifestDependencies {
+    pub fn new(manifest: &Manifest, resolved: &Resolved) -> Self {
+        let identity = |name: &str| {
+            manifest
+                .dependency_identity(name)
+                .or_else(|| resolved.pin(name).map(|pin| pin.identity.as_str()))
+                .map(str::to_string)
+        };
+
+        let mut packages = HashMap::new();
+        let mut conditions = HashMap::new();
+        for target in &manifest.targets {
+            for dependency in &target.dependencies {
+                let (values, package) = match dependency {
+                    TargetDependency::Product(values) => {
+                        let package = TargetDependency::string_at(values, 1)
+                            .map(|package| identity(package).unwrap_or(package.to_lowercase()));
+                        (values, package)
+                    }
+                    // `.byName` only resolves to a product when a dependency shares its name.
+                    TargetDependency::ByName(values) => (
+                        values,
+                        TargetDependency::string_at(values, 0).and_then(identity),
+                    ),
+                    TargetDependency::Target(values) => (values, None),
+                };
+                let Some(name) = TargetDependency::string_at(values, 0) else {
+                    continue;
+                };
+                let key = (target.name.clone(), name.to_string());
+                if let Some(condition) = values.iter().skip(1).find_map(Condition::from_value) {
+                    conditions.insert(key.clone(), condition);
+                }
+                if let Some(package) = package {
+                    packages.insert(key, package);
+                }
+            }
+        }
+        ManifestDependencies {
+            packages,
+            conditions,
+        }
+    }
+
+    pub fn package_for(&self, target: &str, product: &str) -> Option<&str> {
+        self.packages
+            .get(&(target.to_string(), product.to_string()))
+            .map(String::as_str)
+    }
+
+    pub fn condition_for(&self, target: &str, dependency: &str) -> Option<&Condition> {
+        self.conditions
+            .get(&(target.to_string(), dependency.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_version_2_pins() {
+        let resolved = Resolved::from_slice(
+            br#"{"pins": [
+                {"identity": "swift-argument-parser", "kind": "remoteSourceControl",
+                 "location": "https://github.com/apple/swift-argument-parser.git",
+                 "state": {"revision": "0123456789abcdef", "version": "1.2.0"}},
+                {"identity": "swift-log", "kind": "remoteSourceControl",
+                 "location": "https://github.com/apple/swift-log.git",
+                 "state": {"branch": "main", "revision": "fedcba9876543210"}},
+                {"identity": "swift-nio", "kind": "remoteSourceControl",
+                 "location": "https://github.com/apple/swift-nio.git",
+                 "state": {"revision": "fedcba9876543210"}}
+            ], "version": 2}"#,
+        )
+        .unwrap();
+        let states: Vec<(&str, Option<String>)> = resolved
+            .pins
+            .iter()
+            .map(|pin| (pin.identity.as_str(), pin.state()))
+            .collect();
+        assert_eq!(
+            states,
+            [
+                ("swift-argument-parser", Some("1.2.0".to_string())),
+                ("swift-log", Some("main".to_string())),
+                ("swift-nio", Some("fedcba9".to_string())),
+            ]
+        );
+    }
+
+    #[test]
+    fn reads_version_1_pins() {
+        let resolved = Resolved::from_slice(
+            br#"{"object": {"pins": [
+                {"package": "ArgumentParser",
+                 "repositoryURL": "https://github.com/apple/swift-argument-parser.git",
+                 "state": {"branch": null, "revision": "0123456789abcdef", "version": "1.0.0"}}
+            ]}, "version": 1}"#,
+        )
+        .unwrap();
+        let pin = resolved.pin("ArgumentParser").unwrap();
+        assert_eq!(pin.identity, "swift-argument-parser");
+        assert_eq!(pin.state().as_deref(), Some("1.0.0"));
+        assert!(resolved.pin("Swift-Argument-Parser").is_some());
+    }
+}
@@ -0,0 +1,186 @@
+use crate::graph::{Edge, EdgeKind, Graph, Node, NodeKind};
+use crate::package::TargetType;
+use std::collections::{BTreeSet, HashMap};
+use std::fmt::{self, Write};
+
+/// Renders the graph as a Mermaid flowchart. `clusters` wraps the package's own nodes, and each
+/// external package's products, in subgraphs.
+pub fn render(graph: &Graph, clusters: bool) -> String {
+    let mut out = String::new();
+    write_flowchart(&mut out, graph, clusters).expect("formatting into a String cannot fail");
+    out
+}
+
+fn write_flowchart(out: &mut String, graph: &Graph, clusters: bool) -> fmt::Result {
+    writeln!(out, "flowchart TD")?;
+
+    // Mermaid ids are restricted, so nodes are numbered and named by their label.
+    let mut ids: HashMap<&str, String> = HashMap::new();
+    for (index, node) in graph.nodes.iter().enumerate() {
+        ids.insert(&node.id, format!("n{}", index));
+    }
+    let mut classes = BTreeSet::new();
+
+    if clusters {
+        writeln!(out, "    subgraph local[\"{}\"]", escape(&graph.name))?;
+    }
+    for node in graph.local_nodes() {
+        write_node(out, &ids, node, clusters, &mut classes)?;
+    }
+    if clusters {
+        writeln!(out, "    end")?;
+    }
+
+    for node in graph.external_products(None) {
+        write_node(out, &ids, node, clusters, &mut classes)?;
+    }
+    for (index, package) in graph.packages.iter().enumerate() {
+        if clusters {
+            let label = match &package.state {
+                Some(state) => format!("{}<br>{}", escape(&package.identity), escape(state)),
+                None => escape(&package.identity),
+            };
+            writeln!(out, "    subgraph package{}[\"{}\"]", index, label)?;
+        }
+        for node in graph.external_products(Some(&package.identity)) {
+            write_node(out, &ids, node, clusters, &mut classes)?;
+        }
+        if clusters {
+            writeln!(out, "    end")?;
+        }
+    }
+
+    for edge in &graph.edges {
+        let arrow = if edge.redundant || edge.kind == EdgeKind::Vends {
+            "-.->"
+        } else {
+            "-->"
+        };
+        for id in [&edge.from, &edge.to] {
+            if !ids.contains_key(id.as_str()) {
+                let mermaid_id = format!("n{}", ids.len());
+                writeln!(out, "    {}[\"{}\"]", mermaid_id, escape(id))?;
+                ids.insert(id, mermaid_id);
+            }
+        }
+        writeln!(
+            out,
+            "    {} {} {}",
+            ids[edge.from.as_str()],
+            arrow,
+            ids[edge.to.as_str()]
+        )?;
+    }
+
+    // Later styles win, so edges that are both redundant and in a cycle are red.
+    write_link_style(out, graph, "stroke:#9ca3af,stroke-dasharray:5 3", |edge| {
+        edge.redundant
+    })?;
+    write_link_style(out, graph, "stroke:#dc2626", |edge| edge.in_cycle)?;
+
+    for class in classes {
+        writeln!(out, "    classDef {} {}", class, class_style(class))?;
+    }
+    Ok(())
+}
+
+/// Styles the selected edges. Mermaid refers to edges by their position among all the edges in the
+/// diagram.
+fn write_link_style(
+    out: &mut String,
+    graph: &Graph,
+    style: &str,
+    selected: impl Fn(&Edge) -> bool,
+) -> fmt::Result {
+    let indices: Vec<String> = graph
+        .edges
+        .iter()
+        .enumerate()
+        .filter(|(_, edge)| selected(edge))
+        .map(|(index, _)| index.to_string())
+        .collect();
+    if indices.is_empty() {
+        return Ok(());
+    }
+    writeln!(out, "    linkStyle {} {}", indices.join(","), style)
+}
+
+fn write_node(
+    out: &mut String,
+    ids: &HashMap<&str, String>,
+    node: &Node,
+    clusters: bool,
+    classes: &mut BTreeSet<&'static str>,
+) -> fmt::Result {
+    let name = escape(&node.name);
+    let (shape, class) = match &node.kind {
+        NodeKind::Target(target_type) => {
+            let (open, close) = target_shape(target_type);
+            (
+                format!("{}\"{}\"{}", open, name, close),
+                target_class(target_type),
+            )
+        }
+        NodeKind::Product(product_type) => (
+            format!("[/\"{}<br>({})\"/]", name, product_type.description()),
+            "product",
+        ),
+        NodeKind::ExternalProduct {
+            package: Some(package),
+        } if !clusters => (
+            format!("[\"{}<br>({})\"]", name, escape(package)),
+            "external",
+        ),
+        NodeKind::ExternalProduct { .. } => (format!("[\"{}\"]", name), "external"),
+    };
+    classes.insert(class);
+    writeln!(out, "    {}{}:::{}", ids[node.id.as_str()], shape, class)
+}
+
+fn target_shape(target_type: &TargetType) -> (&'static str, &'static str) {
+    match target_type {
+        TargetType::Executable => ("[[", "]]"),
+        TargetType::Library => ("[", "]"),
+        TargetType::Macro => ("{{", "}}"),
+        TargetType::Test => ("([", "])"),
+        TargetType::Plugin => ("[/", "\\]"),
+        TargetType::SystemTarget => ("[(", ")]"),
+        TargetType::Binary => ("[\\", "/]"),
+        TargetType::Snippet => (">", "]"),
+        TargetType::Unknown => ("(", ")"),
+    }
+}
+
+fn target_class(target_type: &TargetType) -> &'static str {
+    match target_type {
+        TargetType::Executable => "executable",
+        TargetType::Library => "library",
+        TargetType::Macro => "macro",
+        TargetType::Test => "test",
+        TargetType::Plugin => "plugin",
+        TargetType::SystemTarget => "systemTarget",
+        TargetType::Binary => "binary",
+        TargetType::Snippet => "snippet",
+        TargetType::Unknown => "unknown",
+    }
+}
+
+fn class_style(class: &str) -> &'static str {
+    match class {
+        "executable" => "fill:#fef3c7,stroke:#92400e",
+        "library" => "fill:#dbeafe,stroke:#1e3a8a",
+        "macro" => "fill:#ede9fe,stroke:#5b21b6",
+        "test" => "fill:#f3f4f6,stroke:#6b7280,stroke-dasharray:4",
+        "plugin" => "fill:#fce7f3,stroke:#9d174d",
+        "systemTarget" | "binary" => "fill:#e5e7eb,stroke:#374151",
+        "snippet" => "fill:#ecfccb,stroke:#3f6212",
+        "product" => "fill:#dcfce7,stroke:#166534",
+        "external" => "fill:#e0f2fe,stroke:#0369a1",
+        _ => "fill:#ffffff,stroke:#000000",
+    }
+}
+
+/// Quoted Mermaid labels cannot contain `"`, which has to be written as an entity instead.
+fn escape(text: &str) -> String {
+    text.replace('"', "#quot;")
+}
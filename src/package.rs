@@ -0,0 +1,171 @@
+use serde::Deserialize;
+use serde_json::Value;
+
+/// The output of `swift package describe --type json`.
+#[derive(Debug, Deserialize)]
+pub struct Package {
+    pub name: String,
+    pub targets: Vec<Target>,
+    #[serde(default)]
+    pub products: Vec<Product>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Product {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub product_type: ProductType,
+    pub targets: Vec<String>,
+}
+
+/// SwiftPM encodes product types as single-key objects, e.g. `{"library": ["automatic"]}` or
+/// `{"executable": null}`.
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(from = "Value")]
+pub enum ProductType {
+    Library(LibraryType),
+    Executable,
+    Plugin,
+    Snippet,
+    Test,
+    Macro,
+    /// A product kind introduced by a newer SwiftPM than this tool knows about, whatever its
+    /// payload.
+    Unknown,
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum LibraryType {
+    Static,
+    Dynamic,
+    Automatic,
+    /// A library type introduced by a newer SwiftPM than this tool knows about.
+    #[serde(other)]
+    Unknown,
+}
+
+impl From<Value> for ProductType {
+    fn from(value: Value) -> Self {
+        let (kind, payload) = match &value {
+            Value::Object(object) if object.len() == 1 => {
+                let (kind, payload) = object.iter().next().expect("one entry");
+                (kind.as_str(), Some(payload))
+            }
+            Value::String(kind) => (kind.as_str(), None),
+            _ => return ProductType::Unknown,
+        };
+        match kind {
+            "library" => ProductType::Library(
+                payload
+                    .and_then(|payload| payload.get(0))
+                    .and_then(|library_type| LibraryType::deserialize(library_type).ok())
+                    .unwrap_or(LibraryType::Unknown),
+            ),
+            "executable" => ProductType::Executable,
+            "plugin" => ProductType::Plugin,
+            "snippet" => ProductType::Snippet,
+            "test" => ProductType::Test,
+            "macro" => ProductType::Macro,
+            _ => ProductType::Unknown,
+        }
+    }
+}
+
+impl ProductType {
+    pub fn description(&self) -> &'static str {
+        match self {
+            ProductType::Library(LibraryType::Static) => "static library",
+            ProductType::Library(LibraryType::Dynamic) => "dynamic library",
+            ProductType::Library(LibraryType::Automatic | LibraryType::Unknown) => "library",
+            ProductType::Executable => "executable",
+            ProductType::Plugin => "plugin",
+            ProductType::Snippet => "snippet",
+            ProductType::Test => "test",
+            ProductType::Macro => "macro",
+            ProductType::Unknown => "product",
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Target {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub target_type: TargetType,
+    /// Relative to the package directory.
+    #[serde(default)]
+    pub path: Option<String>,
+    #[serde(default)]
+    pub sources: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub product_dependencies: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub target_dependencies: Option<Vec<String>>,
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+pub enum TargetType {
+    Executable,
+    Library,
+    Macro,
+    Test,
+    Plugin,
+    SystemTarget,
+    Binary,
+    Snippet,
+    /// A target kind introduced by a newer SwiftPM than this tool knows about.
+    #[serde(other)]
+    Unknown,
+}
+
+impl TargetType {
+    /// The name SwiftPM uses for the target type.
+    pub fn name(&self) -> &'static str {
+        match self {
+            TargetType::Executable => "executable",
+            TargetType::Library => "library",
+            TargetType::Macro => "macro",
+            TargetType::Test => "test",
+            TargetType::Plugin => "plugin",
+            TargetType::SystemTarget => "system-target",
+            TargetType::Binary => "binary",
+            TargetType::Snippet => "snippet",
+            TargetType::Unknown => "unknown",
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn product_type(json: &str) -> ProductType {
+        serde_json::from_str(json).unwrap()
+    }
+
+    #[test]
+    fn unknown_product_kinds_parse_whatever_their_payload() {
+        assert_eq!(
+            product_type(r#"{"library": ["dynamic"]}"#),
+            ProductType::Library(LibraryType::Dynamic)
+        );
+        assert_eq!(
+            product_type(r#"{"executable": null}"#),
+            ProductType::Executable
+        );
+        assert_eq!(
+            product_type(r#"{"futurekind": ["x"]}"#),
+            ProductType::Unknown
+        );
+        assert_eq!(
+            product_type(r#"{"futurekind": null}"#),
+            ProductType::Unknown
+        );
+        assert_eq!(
+            product_type(r#"{"library": ["future"]}"#),
+            ProductType::Library(LibraryType::Unknown)
+        );
+    }
+}
@@ -0,0 +1,78 @@
+use regex::Regex;
+use std::str::FromStr;
+
+/// A pattern matched against target and product names, either a glob where `*` matches any run of
+/// characters and `?` any single one, or a regular expression when prefixed with `re:`. Both must
+/// match the whole name.
+#[derive(Clone, Debug)]
+pub struct Pattern(Regex);
+
+impl Pattern {
+    pub fn matches(&self, name: &str) -> bool {
+        self.0.is_match(name)
+    }
+}
+
+impl FromStr for Pattern {
+    type Err = String;
+
+    fn from_str(pattern: &str) -> Result<Self, Self::Err> {
+        let invalid = |error: regex::Error| format!("invalid pattern `{}`: {}", pattern, error);
+        let expression = match pattern.strip_prefix("re:") {
+            Some(expression) => {
+                // Checked on its own first so errors point into the expression as given.
+                Regex::new(expression).map_err(invalid)?;
+                format!("^(?:{})$", expression)
+            }
+            None => {
+                let mut expression = String::from("^");
+                for character in pattern.chars() {
+                    match character {
+                        '*' => expression.push_str(".*"),
+                        '?' => expression.push('.'),
+                        character => expression.push_str(&regex::escape(&character.to_string())),
+                    }
+                }
+                expression.push('$');
+                expression
+            }
+        };
+        Regex::new(&expression).map(Pattern).map_err(invalid)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pattern(text: &str) -> Pattern {
+        text.parse().unwrap()
+    }
+
+    #[test]
+    fn globs_match_whole_names_literally() {
+        let glob = pattern("Swift.Core+*");
+        assert!(glob.matches("Swift.Core+"));
+        assert!(glob.matches("Swift.Core+Extras"));
+        assert!(!glob.matches("SwiftXCore+"));
+        assert!(!glob.matches("Swift.CoreeExtras"));
+        assert!(!glob.matches("MySwift.Core+"));
+
+        let glob = pattern("Core?");
+        assert!(glob.matches("Core2"));
+        assert!(!glob.matches("Core"));
+        assert!(!glob.matches("Core23"));
+    }
+
+    #[test]
+    fn regular_expressions_match_whole_names() {
+        let expression = pattern("re:Core|Kit");
+        assert!(expression.matches("Core"));
+        assert!(expression.matches("Kit"));
+        assert!(!expression.matches("CoreKit"));
+        assert!(!expression.matches("DemoKit"));
+
+        let error = "re:Core(".parse::<Pattern>().unwrap_err();
+        assert!(error.starts_with("invalid pattern `re:Core(`"), "{}", error);
+    }
+}
@@ -0,0 +1,107 @@
+use crate::graph::{EdgeKind, Graph, Node, NodeKind};
+use std::collections::HashMap;
+use std::fmt::{self, Write};
+
+/// Renders the graph as a PlantUML component diagram: the package and each external package
+/// become `package` blocks, targets become components stereotyped by their type, products become
+/// interfaces, and external products become `<<external>>` components.
+pub fn render(graph: &Graph) -> String {
+    let mut out = String::new();
+    write_diagram(&mut out, graph).expect("formatting into a String cannot fail");
+    out
+}
+
+fn write_diagram(out: &mut String, graph: &Graph) -> fmt::Result {
+    writeln!(out, "@startuml")?;
+    writeln!(out, "title {}", graph.name)?;
+    writeln!(out, "skinparam component {{")?;
+    writeln!(out, "  BackgroundColor<<executable>> #FEF3C7")?;
+    writeln!(out, "  BackgroundColor<<test>> #F3F4F6")?;
+    writeln!(out, "  BackgroundColor<<macro>> #EDE9FE")?;
+    writeln!(out, "  BackgroundColor<<plugin>> #FCE7F3")?;
+    writeln!(out, "  BackgroundColor<<external>> #E0F2FE")?;
+    writeln!(out, "}}")?;
+
+    // PlantUML aliases must be plain identifiers, so nodes are numbered.
+    let mut aliases: HashMap<&str, String> = HashMap::new();
+    for (index, node) in graph.nodes.iter().enumerate() {
+        aliases.insert(&node.id, format!("n{}", index));
+    }
+
+    writeln!(out, "package \"{}\" {{", escape(&graph.name))?;
+    for node in graph.local_nodes() {
+        write_node(out, &aliases, node)?;
+    }
+    writeln!(out, "}}")?;
+
+    for package in &graph.packages {
+        let name = match &package.state {
+            Some(state) => format!("{} ({})", package.identity, state),
+            None => package.identity.clone(),
+        };
+        writeln!(out, "package \"{}\" {{", escape(&name))?;
+        for node in graph.external_products(Some(&package.identity)) {
+            write_node(out, &aliases, node)?;
+        }
+        writeln!(out, "}}")?;
+    }
+    // Products whose package could not be resolved stand on their own.
+    for node in graph.external_products(None) {
+        write_node(out, &aliases, node)?;
+    }
+
+    for edge in &graph.edges {
+        for id in [&edge.from, &edge.to] {
+            if !aliases.contains_key(id.as_str()) {
+                let alias = format!("n{}", aliases.len());
+                writeln!(out, "component \"{}\" as {}", escape(id), alias)?;
+                aliases.insert(id, alias);
+            }
+        }
+        let arrow = match edge.kind {
+            _ if edge.in_cycle && edge.redundant => "-[#red,dashed]->",
+            _ if edge.in_cycle => "-[#red]->",
+            _ if edge.redundant => "-[#gray,dashed]->",
+            EdgeKind::Vends => "..>",
+            EdgeKind::Target | EdgeKind::Product => "-->",
+        };
+        writeln!(
+            out,
+            "{} {} {}",
+            aliases[edge.from.as_str()],
+            arrow,
+            aliases[edge.to.as_str()]
+        )?;
+    }
+
+    writeln!(out, "@enduml")
+}
+
+fn write_node(out: &mut String, aliases: &HashMap<&str, String>, node: &Node) -> fmt::Result {
+    let alias = &aliases[node.id.as_str()];
+    let name = escape(&node.name);
+    match &node.kind {
+        NodeKind::Target(target_type) => writeln!(
+            out,
+            "  component \"{}\" as {} <<{}>>",
+            name,
+            alias,
+            target_type.name()
+        ),
+        NodeKind::Product(product_type) => writeln!(
+            out,
+            "  interface \"{}\" as {} <<{}>>",
+            name,
+            alias,
+            product_type.description()
+        ),
+        NodeKind::ExternalProduct { .. } => {
+            writeln!(out, "  component \"{}\" as {} <<external>>", name, alias)
+        }
+    }
+}
+
+/// PlantUML has no escape for `"` inside quoted names.
+fn escape(text: &str) -> String {
+    text.replace('"', "'")
+}
@@ -0,0 +1,439 @@
+use crate::graph::{EdgeKind, Graph, NodeKind};
+use clap::ValueEnum;
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::fmt::{self, Write};
+
+/// How `stats` prints its report.
+#[derive(Clone, Copy, Debug, Default, PartialEq, ValueEnum)]
+pub enum StatsFormat {
+    #[default]
+    Table,
+    Json,
+    /// One row per target, without the package totals.
+    Csv,
+}
+
+/// Coupling metrics for the package's targets, computed from their target and product
+/// dependencies.
+#[derive(Serialize)]
+pub struct Stats<'a> {
+    targets: Vec<TargetStats<'a>>,
+    totals: Totals<'a>,
+}
+
+#[derive(Serialize)]
+struct TargetStats<'a> {
+    name: &'a str,
+    #[serde(rename = "type")]
+    target_type: &'static str,
+    /// The number of targets depending on this one (afferent coupling, Ca).
+    fan_in: usize,
+    /// The number of targets and external products this one depends on (efferent coupling, Ce).
+    fan_out: usize,
+    /// The length of the longest chain of dependents above the target, 0 for roots.
+    depth: usize,
+    /// Ce / (Ca + Ce), from 0 for targets only depended on to 1 for targets only depending on
+    /// others. Undefined for targets without any dependencies or dependents.
+    instability: Option<f64>,
+}
+
+#[derive(Serialize)]
+struct Totals<'a> {
+    targets: usize,
+    targets_per_type: BTreeMap<&'static str, usize>,
+    external_products: usize,
+    dependencies: usize,
+    /// The longest chain of targets, each depending on the next.
+    longest_chain: Vec<&'a str>,
+}
+
+impl<'a> Stats<'a> {
+    /// Computes the metrics over the target and product dependency edges in the graph. Targets
+    /// that depend on each other share a depth, and chains visit each of them at most once.
+    pub fn new(graph: &'a Graph) -> Self {
+        let targets: Vec<(&str, &str, &'static str)> = graph
+            .nodes
+            .iter()
+            .filter_map(|node| match &node.kind {
+                NodeKind::Target(target_type) => {
+                    Some((node.id.as_str(), node.name.as_str(), target_type.name()))
+                }
+                _ => None,
+            })
+            .collect();
+        let index: HashMap<&str, usize> = targets
+            .iter()
+            .enumerate()
+            .map(|(index, (id, _, _))| (*id, index))
+            .collect();
+        let dependencies: Vec<_> = graph
+            .edges
+            .iter()
+            .filter(|edge| edge.kind != EdgeKind::Vends && index.contains_key(edge.from.as_str()))
+            .collect();
+
+        let mut fan_in = vec![0; targets.len()];
+        let mut fan_out = vec![0; targets.len()];
+        for edge in &dependencies {
+            fan_out[index[edge.from.as_str()]] += 1;
+            if let Some(&to) = index.get(edge.to.as_str()) {
+                fan_in[to] += 1;
+            }
+        }
+
+        // Targets that depend on each other count as one when following dependencies, so they
+        // share a depth and chains pass through them rather than going round.
+        let mut component = vec![usize::MAX; targets.len()];
+        let mut components = 0;
+        let cycles = graph.cycles();
+        for target in 0..targets.len() {
+            if component[target] != usize::MAX {
+                continue;
+            }
+            let cycle = cycles
+                .iter()
+                .find(|cycle| cycle.nodes.iter().any(|id| id == targets[target].0));
+            for member in cycle.map_or(vec![target], |cycle| {
+                cycle
+                    .nodes
+                    .iter()
+                    .filter_map(|id| index.get(id.as_str()).copied())
+                    .collect()
+            }) {
+                component[member] = components;
+            }
+            components += 1;
+        }
+        let mut children = vec![Vec::new(); targets.len()];
+        // The dependencies between components, with the edge between targets each one stands for.
+        let mut component_children = vec![Vec::new(); components];
+        for edge in &dependencies {
+            let Some(&to) = index.get(edge.to.as_str()) else {
+                continue;
+            };
+            let from = index[edge.from.as_str()];
+            children[from].push(to);
+            if component[from] != component[to] {
+                component_children[component[from]].push((component[to], from, to));
+            }
+        }
+        let order = topological_order(
+            &component_children
+                .iter()
+                .map(|edges| edges.iter().map(|&(child, _, _)| child).collect())
+                .collect::<Vec<_>>(),
+        );
+        let mut component_depth = vec![0; components];
+        for &parent in &order {
+            for &(child, _, _) in &component_children[parent] {
+                component_depth[child] = component_depth[child].max(component_depth[parent] + 1);
+            }
+        }
+        // The longest chain down from each component, and the edge it continues along.
+        let mut height = vec![0; components];
+        let mut next = vec![None; components];
+        for &parent in order.iter().rev() {
+            for &(child, from, to) in &component_children[parent] {
+                if height[child] + 1 > height[parent] {
+                    height[parent] = height[child] + 1;
+                    next[parent] = Some((child, from, to));
+                }
+            }
+        }
+        let start = (0..components)
+            .max_by_key(|&start| (height[start], usize::MAX - start))
+            .unwrap_or_default();
+        let mut longest_chain = Vec::new();
+        let mut link = component.iter().position(|&other| other == start);
+        let mut current = start;
+        while let Some(target) = link {
+            longest_chain.push(targets[target].1);
+            link = match next[current] {
+                Some((child, from, to)) => {
+                    // Go round the cycle to the target the chain leaves it from.
+                    for within in path_within(&children, &component, target, from) {
+                        longest_chain.push(targets[within].1);
+                    }
+                    current = child;
+                    Some(to)
+                }
+                None => None,
+            };
+        }
+
+        let mut targets_per_type = BTreeMap::new();
+        for (_, _, target_type) in &targets {
+            *targets_per_type.entry(*target_type).or_insert(0) += 1;
+        }
+        Stats {
+            targets: targets
+                .iter()
+                .enumerate()
+                .map(|(i, &(_, name, target_type))| TargetStats {
+                    name,
+                    target_type,
+                    fan_in: fan_in[i],
+                    fan_out: fan_out[i],
+                    depth: component_depth[component[i]],
+                    instability: (fan_in[i] + fan_out[i] > 0)
+                        .then(|| fan_out[i] as f64 / (fan_in[i] + fan_out[i]) as f64),
+                })
+                .collect(),
+            totals: Totals {
+                targets: targets.len(),
+                targets_per_type,
+                external_products: graph
+                    .nodes
+                    .iter()
+                    .filter(|node| matches!(node.kind, NodeKind::ExternalProduct { .. }))
+                    .count(),
+                dependencies: dependencies.len(),
+                longest_chain,
+            },
+        }
+    }
+
+    pub fn render(&self, format: StatsFormat) -> String {
+        let mut out = String::new();
+        match format {
+            StatsFormat::Table => self.write_table(&mut out),
+            StatsFormat::Json => {
+                out = serde_json::to_string_pretty(self).expect("stats serialize to JSON");
+                out.push('\n');
+                Ok(())
+            }
+            StatsFormat::Csv => self.write_csv(&mut out),
+        }
+        .expect("formatting into a String cannot fail");
+        out
+    }
+
+    fn write_table(&self, out: &mut String) -> fmt::Result {
+        let header = [
+            "Target",
+            "Type",
+            "Fan-in",
+            "Fan-out",
+            "Depth",
+            "Instability",
+        ];
+        let rows: Vec<[String; 6]> = self
+            .targets
+            .iter()
+            .map(|target| {
+                [
+                    target.name.to_string(),
+                    target.target_type.to_string(),
+                    target.fan_in.to_string(),
+                    target.fan_out.to_string(),
+                    target.depth.to_string(),
+                    target
+                        .instability
+                        .map_or("-".to_string(), |instability| format!("{:.2}", instability)),
+                ]
+            })
+            .collect();
+        let mut widths = header.map(str::len);
+        for row in &rows {
+            for (width, cell) in widths.iter_mut().zip(row) {
+                *width = (*width).max(cell.chars().count());
+            }
+        }
+        let header = header.map(str::to_string);
+        for row in std::iter::once(&header).chain(&rows) {
+            let mut line = String::new();
+            for (column, (cell, width)) in row.iter().zip(widths).enumerate() {
+                // Names are left aligned, numbers right aligned.
+                match column {
+                    0 | 1 => write!(line, "{:<width$}  ", cell, width = width)?,
+                    _ => write!(line, "{:>width$}  ", cell, width = width)?,
+                }
+            }
+            writeln!(out, "{}", line.trim_end())?;
+        }
+
+        let totals = &self.totals;
+        let types: Vec<String> = totals
+            .targets_per_type
+            .iter()
+            .map(|(target_type, count)| format!("{} {}", count, target_type))
+            .collect();
+        writeln!(out)?;
+        write!(out, "Targets: {}", totals.targets)?;
+        if !types.is_empty() {
+            write!(out, " ({})", types.join(", "))?;
+        }
+        writeln!(out)?;
+        writeln!(out, "External products: {}", totals.external_products)?;
+        writeln!(out, "Dependencies: {}", totals.dependencies)?;
+        writeln!(out, "Longest chain: {}", totals.longest_chain.join(" -> "))
+    }
+
+    fn write_csv(&self, out: &mut String) -> fmt::Result {
+        writeln!(out, "target,type,fan_in,fan_out,depth,instability")?;
+        for target in &self.targets {
+            writeln!(
+                out,
+                "{},{},{},{},{},{}",
+                csv_field(target.name),
+                target.target_type,
+                target.fan_in,
+                target.fan_out,
+                target.depth,
+                target
+                    .instability
+                    .map_or(String::new(), |instability| format!("{:.4}", instability))
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Quotes a field if it contains characters with a meaning in CSV.
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+/// Orders the nodes of an acyclic graph so that every node comes before its children.
+fn topological_order(children: &[Vec<usize>]) -> Vec<usize> {
+    let mut parents = vec![0; children.len()];
+    for &child in children.iter().flatten() {
+        parents[child] += 1;
+    }
+    let mut order: Vec<usize> = (0..children.len())
+        .filter(|&node| parents[node] == 0)
+        .collect();
+    let mut next = 0;
+    while let Some(&node) = order.get(next) {
+        next += 1;
+        for &child in &children[node] {
+            parents[child] -= 1;
+            if parents[child] == 0 {
+                order.push(child);
+            }
+        }
+    }
+    order
+}
+
+/// The targets after `from` on a shortest path to `to` that stays within their component, or
+/// nothing if `from` is `to`.
+fn path_within(children: &[Vec<usize>], component: &[usize], from: usize, to: usize) -> Vec<usize> {
+    let mut previous = HashMap::from([(from, from)]);
+    let mut queue = VecDeque::from([from]);
+    while let Some(node) = queue.pop_front() {
+        for &child in &children[node] {
+            if component[child] == component[from] && !previous.contains_key(&child) {
+                previous.insert(child, node);
+                queue.push_back(child);
+            }
+        }
+    }
+    let mut path = Vec::new();
+    let mut node = to;
+    while node != from {
+        path.push(node);
+        node = previous[&node];
+    }
+    path.reverse();
+    path
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::GraphOptions;
+    use crate::manifest::{ManifestDependencies, Resolved};
+    use crate::package::Package;
+
+    fn graph(targets: &str) -> Graph {
+        let package: Package =
+            serde_json::from_str(&format!(r#"{{"name": "Metrics", "targets": {}}}"#, targets))
+                .unwrap();
+        Graph::new(
+            &package,
+            &ManifestDependencies::default(),
+            &Resolved::default(),
+            &GraphOptions::default(),
+        )
+    }
+
+    /// Each target's name, fan-in, fan-out and depth.
+    fn metrics<'a>(stats: &'a Stats) -> Vec<(&'a str, usize, usize, usize)> {
+        stats
+            .targets
+            .iter()
+            .map(|target| (target.name, target.fan_in, target.fan_out, target.depth))
+            .collect()
+    }
+
+    #[test]
+    fn measures_coupling_and_depth() {
+        let graph = graph(
+            r#"[
+                {"name": "App", "type": "executable", "target_dependencies": ["Feature", "Core"]},
+                {"name": "Feature", "type": "library", "target_dependencies": ["Core"],
+                 "product_dependencies": ["Logging"]},
+                {"name": "Core", "type": "library"},
+                {"name": "Tool", "type": "executable", "target_dependencies": ["Helper"]},
+                {"name": "Helper", "type": "library", "target_dependencies": ["Base"]},
+                {"name": "Base", "type": "library"}
+            ]"#,
+        );
+        let stats = Stats::new(&graph);
+        assert_eq!(
+            metrics(&stats),
+            [
+                ("App", 0, 2, 0),
+                ("Feature", 1, 2, 1),
+                ("Core", 2, 0, 2),
+                ("Tool", 0, 1, 0),
+                ("Helper", 1, 1, 1),
+                ("Base", 1, 0, 2),
+            ]
+        );
+        let instability: Vec<Option<String>> = stats
+            .targets
+            .iter()
+            .map(|target| target.instability.map(|value| format!("{:.2}", value)))
+            .collect();
+        assert_eq!(
+            instability,
+            ["1.00", "0.67", "0.00", "1.00", "0.50", "0.00"].map(|value| Some(value.to_string()))
+        );
+        assert_eq!(stats.totals.external_products, 1);
+        assert_eq!(stats.totals.dependencies, 6);
+        // Both chains are as long, so the one starting at the earlier target is reported.
+        assert_eq!(stats.totals.longest_chain, ["App", "Feature", "Core"]);
+    }
+
+    #[test]
+    fn targets_in_a_cycle_share_a_depth() {
+        let graph = graph(
+            r#"[
+                {"name": "A", "type": "library", "target_dependencies": ["B"]},
+                {"name": "B", "type": "library", "target_dependencies": ["C"]},
+                {"name": "C", "type": "library", "target_dependencies": ["A", "D"]},
+                {"name": "D", "type": "library"},
+                {"name": "E", "type": "library", "target_dependencies": ["A"]}
+            ]"#,
+        );
+        let stats = Stats::new(&graph);
+        assert_eq!(
+            metrics(&stats),
+            [
+                ("A", 2, 1, 1),
+                ("B", 1, 1, 1),
+                ("C", 1, 2, 1),
+                ("D", 1, 0, 2),
+                ("E", 0, 1, 0),
+            ]
+        );
+        assert_eq!(stats.totals.longest_chain, ["E", "A", "B", "C", "D"]);
+    }
+}
@@ -0,0 +1,268 @@
+use crate::graph::{EdgeKind, Graph, NodeKind};
+use crate::layout::{self, Position};
+use crate::package::TargetType;
+use crate::xml::escape;
+use std::collections::HashMap;
+use std::fmt::{self, Write};
+
+const FONT_SIZE: usize = 14;
+/// An estimate of the average character width at `FONT_SIZE`, as text cannot be measured here.
+const CHARACTER_WIDTH: usize = 8;
+const PADDING: usize = 12;
+const NODE_HEIGHT: usize = 44;
+const LAYER_GAP: usize = 56;
+const NODE_GAP: usize = 24;
+/// The width reserved for an edge passing through a layer.
+const EDGE_WIDTH: usize = 12;
+const MARGIN: usize = 16;
+/// How far the loop for a node depending on itself reaches to the right of the node.
+const LOOP_WIDTH: usize = 24;
+
+/// A node as drawn: its label, an optional second line, and its colors.
+struct Shape<'a> {
+    id: &'a str,
+    label: &'a str,
+    detail: Option<String>,
+    fill: &'static str,
+    stroke: &'static str,
+    rounded: bool,
+    dashed: bool,
+}
+
+/// Renders the graph as SVG with the built-in layered layout, so that no Graphviz install is
+/// needed. The layout is simpler than Graphviz's, which remains available with `--graphviz`.
+pub fn render(graph: &Graph) -> String {
+    let mut out = String::new();
+    write_svg(&mut out, graph).expect("formatting into a String cannot fail");
+    out
+}
+
+fn write_svg(out: &mut String, graph: &Graph) -> fmt::Result {
+    let missing = graph.missing_nodes();
+    let shapes: Vec<Shape> = graph
+        .nodes
+        .iter()
+        .map(|node| {
+            let (detail, fill, stroke) = match &node.kind {
+                NodeKind::Target(target_type) => {
+                    let (fill, stroke) = target_colors(target_type);
+                    (None, fill, stroke)
+                }
+                NodeKind::Product(product_type) => (
+                    Some(format!("({})", product_type.description())),
+                    "#dcfce7",
+                    "#166534",
+                ),
+                NodeKind::ExternalProduct { package } => (
+                    package.as_ref().map(|package| format!("({})", package)),
+                    "#e0f2fe",
+                    "#0369a1",
+                ),
+            };
+            Shape {
+                id: &node.id,
+                label: &node.name,
+                detail,
+                fill,
+                stroke,
+                rounded: !matches!(node.kind, NodeKind::Target(_)),
+                dashed: node.kind == NodeKind::Target(TargetType::Test),
+            }
+        })
+        .chain(missing.iter().map(|&id| Shape {
+            id,
+            label: id,
+            detail: None,
+            fill: "#ffffff",
+            stroke: "#6b7280",
+            rounded: false,
+            dashed: true,
+        }))
+        .collect();
+    let index: HashMap<&str, usize> = shapes
+        .iter()
+        .enumerate()
+        .map(|(index, shape)| (shape.id, index))
+        .collect();
+    let widths: Vec<usize> = shapes
+        .iter()
+        .map(|shape| {
+            let characters = shape.label.chars().count().max(
+                shape
+                    .detail
+                    .as_ref()
+                    .map_or(0, |detail| detail.chars().count()),
+            );
+            characters * CHARACTER_WIDTH + 2 * PADDING
+        })
+        .collect();
+    let edges: Vec<(usize, usize)> = graph
+        .edges
+        .iter()
+        .map(|edge| (index[edge.from.as_str()], index[edge.to.as_str()]))
+        .collect();
+    let layout = layout::layout(&widths, &edges, EDGE_WIDTH, NODE_GAP);
+
+    let loops = edges.iter().any(|(from, to)| from == to);
+    let width = layout.width + 2 * MARGIN + if loops { LOOP_WIDTH } else { 0 };
+    let height =
+        (layout.layer_count * (NODE_HEIGHT + LAYER_GAP)).saturating_sub(LAYER_GAP) + 2 * MARGIN;
+    writeln!(
+        out,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{0}" height="{1}" viewBox="0 0 {0} {1}" font-family="Helvetica, Arial, sans-serif" font-size="{2}">"#,
+        width, height, FONT_SIZE
+    )?;
+    writeln!(out, "  <title>{}</title>", escape(&graph.name))?;
+    writeln!(out, "  <defs>")?;
+    writeln!(
+        out,
+        r##"    <marker id="arrow" viewBox="0 0 10 10" refX="10" refY="5" markerWidth="8" markerHeight="8" orient="auto-start-reverse"><path d="M0,0 L10,5 L0,10 z" fill="#4b5563"/></marker>"##
+    )?;
+    writeln!(out, "  </defs>")?;
+    writeln!(out, r#"  <rect width="100%" height="100%" fill="white"/>"#)?;
+
+    for ((edge, route), &(from, to)) in graph.edges.iter().zip(&layout.edges).zip(&edges) {
+        let (dash, mut stroke) = match edge.kind {
+            _ if edge.redundant => (r#" stroke-dasharray="5,3""#, "#9ca3af"),
+            EdgeKind::Vends => (r#" stroke-dasharray="5,3""#, "#4b5563"),
+            EdgeKind::Target | EdgeKind::Product => ("", "#4b5563"),
+        };
+        if edge.in_cycle {
+            stroke = "#dc2626";
+        }
+        let marker = if route.reversed {
+            "marker-start"
+        } else {
+            "marker-end"
+        };
+        write!(out, r#"  <path d=""#)?;
+        if from == to {
+            write_loop_path(out, &layout.nodes[from])?;
+        } else {
+            write_edge_path(out, &route.points)?;
+        }
+        writeln!(
+            out,
+            r#"" fill="none" stroke="{}" stroke-width="1.2"{} {}="url(#arrow)">"#,
+            stroke, dash, marker
+        )?;
+        let mut title = format!("{} → {}", shapes[from].label, shapes[to].label);
+        if let Some(condition) = &edge.condition {
+            let mut restrictions = condition.platforms.clone();
+            restrictions.extend(condition.configuration.clone());
+            title.push_str(&format!(" ({})", restrictions.join(", ")));
+        }
+        writeln!(out, "    <title>{}</title>", escape(&title))?;
+        writeln!(out, "  </path>")?;
+    }
+
+    for (shape, position) in shapes.iter().zip(&layout.nodes) {
+        write_node(out, shape, position)?;
+    }
+    writeln!(out, "</svg>")
+}
+
+fn write_node(out: &mut String, shape: &Shape, position: &Position) -> fmt::Result {
+    let (x, y) = (MARGIN + position.x, top(position.layer));
+    writeln!(out, "  <g>")?;
+    writeln!(out, "    <title>{}</title>", escape(shape.id))?;
+    writeln!(
+        out,
+        r#"    <rect x="{}" y="{}" width="{}" height="{}" rx="{}" fill="{}" stroke="{}"{}/>"#,
+        x,
+        y,
+        position.width,
+        NODE_HEIGHT,
+        if shape.rounded { 12 } else { 2 },
+        shape.fill,
+        shape.stroke,
+        if shape.dashed {
+            r#" stroke-dasharray="4,2""#
+        } else {
+            ""
+        }
+    )?;
+    let center = x + position.width / 2;
+    match &shape.detail {
+        Some(detail) => {
+            writeln!(
+                out,
+                r#"    <text x="{}" y="{}" text-anchor="middle">{}</text>"#,
+                center,
+                y + NODE_HEIGHT / 2 - 3,
+                escape(shape.label)
+            )?;
+            writeln!(
+                out,
+                r##"    <text x="{}" y="{}" text-anchor="middle" font-size="{}" fill="#4b5563">{}</text>"##,
+                center,
+                y + NODE_HEIGHT / 2 + 13,
+                FONT_SIZE - 3,
+                escape(detail)
+            )?;
+        }
+        None => writeln!(
+            out,
+            r#"    <text x="{}" y="{}" text-anchor="middle">{}</text>"#,
+            center,
+            y + NODE_HEIGHT / 2 + 5,
+            escape(shape.label)
+        )?,
+    }
+    writeln!(out, "  </g>")
+}
+
+/// A smooth path from the bottom of the upper node, straight down through the layers the edge
+/// passes, to the top of the lower node.
+fn write_edge_path(out: &mut String, points: &[Position]) -> fmt::Result {
+    let last = points.len() - 1;
+    let mut previous: Option<(usize, usize)> = None;
+    for (i, point) in points.iter().enumerate() {
+        let x = MARGIN + point.center();
+        let entry = (i > 0).then(|| top(point.layer));
+        let exit = (i < last).then(|| top(point.layer) + NODE_HEIGHT);
+        if let (Some((x1, y1)), Some(y2)) = (previous, entry) {
+            let middle = (y1 + y2) / 2;
+            write!(out, "C{},{} {},{} {},{} ", x1, middle, x, middle, x, y2)?;
+        }
+        if let Some(y) = exit {
+            write!(out, "{}{},{} ", if i == 0 { "M" } else { "L" }, x, y)?;
+        }
+        previous = exit.map(|y| (x, y));
+    }
+    Ok(())
+}
+
+/// A loop out of the right side of a node and back into it.
+fn write_loop_path(out: &mut String, position: &Position) -> fmt::Result {
+    let (x, y) = (MARGIN + position.x + position.width, top(position.layer));
+    write!(
+        out,
+        "M{},{} C{},{} {},{} {},{}",
+        x,
+        y + NODE_HEIGHT / 4,
+        x + LOOP_WIDTH,
+        y,
+        x + LOOP_WIDTH,
+        y + NODE_HEIGHT,
+        x,
+        y + NODE_HEIGHT * 3 / 4
+    )
+}
+
+fn top(layer: usize) -> usize {
+    MARGIN + layer * (NODE_HEIGHT + LAYER_GAP)
+}
+
+fn target_colors(target_type: &TargetType) -> (&'static str, &'static str) {
+    match target_type {
+        TargetType::Executable => ("#fef3c7", "#92400e"),
+        TargetType::Library => ("#dbeafe", "#1e3a8a"),
+        TargetType::Macro => ("#ede9fe", "#5b21b6"),
+        TargetType::Test => ("#f3f4f6", "#6b7280"),
+        TargetType::Plugin => ("#fce7f3", "#9d174d"),
+        TargetType::SystemTarget | TargetType::Binary => ("#e5e7eb", "#374151"),
+        TargetType::Snippet => ("#ecfccb", "#3f6212"),
+        TargetType::Unknown => ("#ffffff", "#000000"),
+    }
+}
@@ -0,0 +1,450 @@
+use crate::graph::{Graph, NodeKind};
+use crate::layout::{self, Position};
+use crate::package::TargetType;
+use std::collections::{BTreeMap, HashMap};
+
+/// Options for drawing the graph as text.
+#[derive(Debug, Default)]
+pub struct TerminalOptions {
+    /// Restrict the drawing to ASCII instead of Unicode box-drawing characters.
+    pub ascii: bool,
+    /// Color nodes by their kind and target type with ANSI escape codes.
+    pub color: bool,
+}
+
+const UP: u8 = 1;
+const DOWN: u8 = 2;
+const LEFT: u8 = 4;
+const RIGHT: u8 = 8;
+
+/// The height of a node's box: its top border, label and bottom border.
+const BOX_HEIGHT: usize = 3;
+
+#[derive(Clone, Copy)]
+struct Cell {
+    /// A character drawn explicitly, e.g. part of a box or an arrowhead.
+    character: Option<char>,
+    /// The directions edge lines leave the cell in, combined into a line character when no
+    /// character was drawn explicitly.
+    lines: u8,
+    /// Whether a line for an edge that is not redundant passes through the cell. Lines only used
+    /// by redundant edges are dashed.
+    solid: bool,
+    color: Option<&'static str>,
+}
+
+struct Canvas {
+    rows: Vec<Vec<Cell>>,
+}
+
+impl Canvas {
+    fn new(width: usize, height: usize) -> Self {
+        let blank = Cell {
+            character: None,
+            lines: 0,
+            solid: false,
+            color: None,
+        };
+        Canvas {
+            rows: vec![vec![blank; width]; height],
+        }
+    }
+
+    fn put(&mut self, row: usize, column: usize, character: char, color: Option<&'static str>) {
+        let cell = &mut self.rows[row][column];
+        cell.character = Some(character);
+        cell.color = color;
+    }
+
+    /// Draws a straight line between two cells, except for cells in rows outside `rows`.
+    fn line(
+        &mut self,
+        from: (usize, usize),
+        to: (usize, usize),
+        rows: &std::ops::Range<usize>,
+        dashed: bool,
+    ) {
+        let (mut row, mut column) = from;
+        loop {
+            let mut lines = 0;
+            if (row, column) != to {
+                lines |= direction((row, column), to);
+            }
+            if (row, column) != from {
+                lines |= direction((row, column), from);
+            }
+            if rows.contains(&row) {
+                let cell = &mut self.rows[row][column];
+                cell.lines |= lines;
+                cell.solid |= !dashed;
+            }
+            if (row, column) == to {
+                break;
+            }
+            match direction((row, column), to) {
+                UP => row -= 1,
+                DOWN => row += 1,
+                LEFT => column -= 1,
+                _ => column += 1,
+            }
+        }
+    }
+
+    fn finish(&self, ascii: bool) -> String {
+        let mut out = String::new();
+        for row in &self.rows {
+            let mut color = None;
+            let mut line = String::new();
+            for cell in row {
+                if cell.color != color {
+                    if color.is_some() {
+                        line.push_str("\x1b[0m");
+                    }
+                    if let Some(code) = cell.color {
+                        line.push_str(&format!("\x1b[{}m", code));
+                    }
+                    color = cell.color;
+                }
+                line.push(
+                    cell.character
+                        .unwrap_or_else(|| line_character(cell.lines, !cell.solid, ascii)),
+                );
+            }
+            if color.is_some() {
+                line.push_str("\x1b[0m");
+            }
+            out.push_str(line.trim_end());
+            out.push('\n');
+        }
+        out
+    }
+}
+
+fn direction(from: (usize, usize), to: (usize, usize)) -> u8 {
+    if to.0 < from.0 {
+        UP
+    } else if to.0 > from.0 {
+        DOWN
+    } else if to.1 < from.1 {
+        LEFT
+    } else {
+        RIGHT
+    }
+}
+
+/// The character for a cell with lines leaving it in the `lines` directions. Dashed lines match
+/// the borders of missing nodes' boxes; only straight lines have dashed characters.
+fn line_character(lines: u8, dashed: bool, ascii: bool) -> char {
+    match (lines, dashed, ascii) {
+        (UP | DOWN | 3, true, false) => return '┆',
+        (LEFT | RIGHT | 12, true, false) => return '╌',
+        (UP | DOWN | 3, true, true) => return ':',
+        (LEFT | RIGHT | 12, true, true) => return '.',
+        _ => {}
+    }
+    if ascii {
+        return match lines {
+            0 => ' ',
+            UP | DOWN | 3 => '|',
+            LEFT | RIGHT | 12 => '-',
+            _ => '+',
+        };
+    }
+    match lines {
+        0 => ' ',
+        UP | DOWN | 3 => '│',
+        LEFT | RIGHT | 12 => '─',
+        6 => '┐',
+        10 => '┌',
+        5 => '┘',
+        9 => '└',
+        7 => '┤',
+        11 => '├',
+        14 => '┬',
+        13 => '┴',
+        _ => '┼',
+    }
+}
+
+/// The characters a node's box is drawn with: corners clockwise from the top left, the horizontal
+/// and vertical borders, and the junctions where edges leave the bottom or enter the top.
+struct BoxStyle {
+    corners: [char; 4],
+    horizontal: char,
+    vertical: char,
+    junctions: [char; 2],
+}
+
+fn box_style(kind: Option<&NodeKind>, ascii: bool) -> BoxStyle {
+    let style = |corners: [char; 4], horizontal, vertical, junctions| BoxStyle {
+        corners,
+        horizontal,
+        vertical,
+        junctions,
+    };
+    match (kind, ascii) {
+        (Some(NodeKind::Target(_)), false) => style(['┌', '┐', '┘', '└'], '─', '│', ['┬', '┴']),
+        (Some(NodeKind::Product(_)), false) => style(['╭', '╮', '╯', '╰'], '─', '│', ['┬', '┴']),
+        (Some(NodeKind::ExternalProduct { .. }), false) => {
+            style(['╔', '╗', '╝', '╚'], '═', '║', ['╤', '╧'])
+        }
+        (None, false) => style(['┌', '┐', '┘', '└'], '╌', '┆', ['┬', '┴']),
+        (Some(NodeKind::Target(_)), true) => style(['+', '+', '+', '+'], '-', '|', ['+', '+']),
+        (Some(NodeKind::Product(_)), true) => style(['.', '.', '\'', '\''], '-', '|', ['+', '+']),
+        (Some(NodeKind::ExternalProduct { .. }), true) => {
+            style(['#', '#', '#', '#'], '=', '#', ['+', '+'])
+        }
+        (None, true) => style(['+', '+', '+', '+'], '.', ':', ['+', '+']),
+    }
+}
+
+/// The ANSI SGR code for the arrowheads of edges in a dependency cycle.
+const CYCLE_COLOR: &str = "31";
+
+/// ANSI SGR codes for each kind of node.
+fn color(kind: Option<&NodeKind>) -> &'static str {
+    match kind {
+        Some(NodeKind::Target(target_type)) => match target_type {
+            TargetType::Executable => "33",
+            TargetType::Library => "34",
+            TargetType::Macro | TargetType::Plugin => "35",
+            TargetType::Test => "90",
+            TargetType::SystemTarget | TargetType::Binary => "37",
+            TargetType::Snippet => "32",
+            TargetType::Unknown => "39",
+        },
+        Some(NodeKind::Product(_)) => "32",
+        Some(NodeKind::ExternalProduct { .. }) => "36",
+        None => "2",
+    }
+}
+
+/// Draws the graph with box-drawing characters in layers from the nodes nothing depends on down to
+/// those without dependencies, for viewing in a terminal.
+pub fn render(graph: &Graph, options: &TerminalOptions) -> String {
+    let missing = graph.missing_nodes();
+    let nodes: Vec<(&str, Option<&NodeKind>, String)> = graph
+        .nodes
+        .iter()
+        .map(|node| {
+            let label = match &node.kind {
+                NodeKind::Target(_) => node.name.clone(),
+                NodeKind::Product(product_type) => {
+                    format!("{} ({})", node.name, product_type.description())
+                }
+                NodeKind::ExternalProduct {
+                    package: Some(package),
+                } => format!("{} ({})", node.name, package),
+                NodeKind::ExternalProduct { package: None } => node.name.clone(),
+            };
+            (node.id.as_str(), Some(&node.kind), label)
+        })
+        .chain(missing.iter().map(|&id| (id, None, id.to_string())))
+        .collect();
+    let index: HashMap<&str, usize> = nodes
+        .iter()
+        .enumerate()
+        .map(|(index, (id, _, _))| (*id, index))
+        .collect();
+    let widths: Vec<usize> = nodes
+        .iter()
+        .map(|(_, _, label)| label.chars().count() + 4)
+        .collect();
+    let edges: Vec<(usize, usize)> = graph
+        .edges
+        .iter()
+        .map(|edge| (index[edge.from.as_str()], index[edge.to.as_str()]))
+        .collect();
+    let layout = layout::layout(&widths, &edges, 1, 2);
+
+    // Edge segments between two layers turn horizontal on a track row. Segments leaving the same
+    // column share a track, and otherwise segments whose horizontal parts overlap get tracks of
+    // their own so they do not merge.
+    struct Segment {
+        upper: Position,
+        lower: Position,
+        /// The node at either end, unless the segment ends at a placeholder.
+        upper_node: Option<usize>,
+        lower_node: Option<usize>,
+        reversed: bool,
+        in_cycle: bool,
+        redundant: bool,
+        track: usize,
+    }
+    let mut segments: Vec<Vec<Segment>> = (0..layout.layer_count).map(|_| Vec::new()).collect();
+    for ((route, &(from, to)), edge) in layout.edges.iter().zip(&edges).zip(&graph.edges) {
+        let (upper, lower) = if route.reversed {
+            (to, from)
+        } else {
+            (from, to)
+        };
+        let last = route.points.len().saturating_sub(1);
+        for (i, pair) in route.points.windows(2).enumerate() {
+            segments[pair[0].layer].push(Segment {
+                upper: pair[0],
+                lower: pair[1],
+                upper_node: (i == 0).then_some(upper),
+                lower_node: (i + 1 == last).then_some(lower),
+                reversed: route.reversed,
+                in_cycle: edge.in_cycle,
+                redundant: edge.redundant,
+                track: 0,
+            });
+        }
+    }
+    let mut track_counts = Vec::with_capacity(layout.layer_count);
+    for layer in &mut segments {
+        // The columns spanned by the segments leaving each column.
+        let mut spans: BTreeMap<usize, (usize, usize)> = BTreeMap::new();
+        for segment in layer.iter() {
+            let (start, end) = (segment.upper.center(), segment.lower.center());
+            if start != end {
+                let span = spans.entry(start).or_insert((start, start));
+                *span = (span.0.min(end), span.1.max(end));
+            }
+        }
+        let mut spans: Vec<(usize, (usize, usize))> = spans.into_iter().collect();
+        spans.sort_by_key(|(_, (start, _))| *start);
+        // The last column used on each track.
+        let mut tracks: Vec<usize> = Vec::new();
+        let mut assigned: HashMap<usize, usize> = HashMap::new();
+        for (column, (start, end)) in spans {
+            let track = match tracks.iter().position(|&used| used + 1 < start) {
+                Some(track) => {
+                    tracks[track] = end;
+                    track + 1
+                }
+                None => {
+                    tracks.push(end);
+                    tracks.len()
+                }
+            };
+            assigned.insert(column, track);
+        }
+        for segment in layer.iter_mut() {
+            if segment.upper.center() != segment.lower.center() {
+                segment.track = assigned[&segment.upper.center()];
+            }
+        }
+        track_counts.push(tracks.len());
+    }
+
+    // Each layer is a row of boxes followed by a channel for the edges to the next layer: a row
+    // leaving the boxes, the tracks, and a row for arrowheads.
+    let mut layer_rows = Vec::with_capacity(layout.layer_count);
+    let mut height = 0;
+    for (layer, tracks) in track_counts.iter().enumerate() {
+        layer_rows.push(height);
+        height += BOX_HEIGHT;
+        if layer + 1 < layout.layer_count {
+            height += tracks + 2;
+        }
+    }
+    // One more column for the loops of nodes at the right edge that depend on themselves.
+    let mut canvas = Canvas::new(layout.width + 1, height);
+
+    for (layer, segments) in segments.iter().enumerate() {
+        let Some(&next_row) = layer_rows.get(layer + 1) else {
+            continue;
+        };
+        let channel = layer_rows[layer] + BOX_HEIGHT..next_row;
+        for segment in segments {
+            let (x1, x2) = (segment.upper.center(), segment.lower.center());
+            let top = channel.start - 1;
+            let bottom = channel.end;
+            if segment.track == 0 {
+                canvas.line((top, x1), (bottom, x2), &channel, segment.redundant);
+            } else {
+                let track = channel.start + segment.track;
+                canvas.line((top, x1), (track, x1), &channel, segment.redundant);
+                canvas.line((track, x1), (track, x2), &channel, segment.redundant);
+                canvas.line((track, x2), (bottom, x2), &channel, segment.redundant);
+            }
+            // Lines may be shared between edges, so only the arrowheads show which are in a cycle.
+            let arrow_color = (options.color && segment.in_cycle).then_some(CYCLE_COLOR);
+            if segment.reversed && segment.upper_node.is_some() {
+                let arrow = if options.ascii { '^' } else { '▲' };
+                canvas.put(channel.start, x1, arrow, arrow_color);
+            } else if !segment.reversed && segment.lower_node.is_some() {
+                let arrow = if options.ascii { 'v' } else { '▼' };
+                canvas.put(channel.end - 1, x2, arrow, arrow_color);
+            }
+            // Placeholders for edges spanning several layers are drawn as plain lines.
+            for (position, node, rows) in [
+                (
+                    segment.upper,
+                    segment.upper_node,
+                    layer_rows[layer]..channel.start,
+                ),
+                (
+                    segment.lower,
+                    segment.lower_node,
+                    next_row..next_row + BOX_HEIGHT,
+                ),
+            ] {
+                if node.is_none() {
+                    let column = position.center();
+                    canvas.line(
+                        (rows.start, column),
+                        (rows.end - 1, column),
+                        &rows,
+                        segment.redundant,
+                    );
+                    canvas.rows[rows.start][column].lines |= UP;
+                    canvas.rows[rows.end - 1][column].lines |= DOWN;
+                }
+            }
+            if let Some(node) = segment.upper_node {
+                let style = box_style(nodes[node].1, options.ascii);
+                canvas.put(top, x1, style.junctions[0], None);
+            }
+            if let Some(node) = segment.lower_node.filter(|_| segment.reversed) {
+                let style = box_style(nodes[node].1, options.ascii);
+                canvas.put(bottom, x2, style.junctions[1], None);
+            }
+        }
+    }
+
+    // Boxes are drawn last so edge junctions on their borders take their color.
+    for ((_, kind, label), position) in nodes.iter().zip(&layout.nodes) {
+        let style = box_style(*kind, options.ascii);
+        let color = options.color.then(|| color(*kind));
+        let row = layer_rows[position.layer];
+        let (left, right) = (position.x, position.x + position.width - 1);
+        for column in left + 1..right {
+            for row in [row, row + 2] {
+                if canvas.rows[row][column].character.is_none() {
+                    canvas.put(row, column, style.horizontal, color);
+                } else {
+                    canvas.rows[row][column].color = color;
+                }
+            }
+        }
+        canvas.put(row, left, style.corners[0], color);
+        canvas.put(row, right, style.corners[1], color);
+        canvas.put(row + 2, right, style.corners[2], color);
+        canvas.put(row + 2, left, style.corners[3], color);
+        canvas.put(row + 1, left, style.vertical, color);
+        canvas.put(row + 1, right, style.vertical, color);
+        for (offset, character) in label.chars().enumerate() {
+            canvas.put(row + 1, left + 2 + offset, character, color);
+        }
+    }
+
+    // Nodes depending on themselves get a loop to the right of their label row.
+    for (edge, &(from, to)) in graph.edges.iter().zip(&edges) {
+        if from == to {
+            let position = layout.nodes[from];
+            let arrow_color = (options.color && edge.in_cycle).then_some(CYCLE_COLOR);
+            let character = if options.ascii { '@' } else { '↺' };
+            canvas.put(
+                layer_rows[position.layer] + 1,
+                position.x + position.width,
+                character,
+                arrow_color,
+            );
+        }
+    }
+
+    canvas.finish(options.ascii)
+}
@@ -0,0 +1,49 @@
+use crate::graph::{Edge, Graph, Node};
+
+/// The attributes GraphML and GEXF output record for each node, as `(name, type)` pairs. Types are
+/// GraphML's; GEXF calls `int` `integer`.
+pub const NODE_ATTRIBUTES: &[(&str, &str)] = &[
+    ("kind", "string"),
+    ("type", "string"),
+    ("package", "string"),
+    ("path", "string"),
+    ("sources", "int"),
+];
+
+/// The attributes recorded for each edge.
+pub const EDGE_ATTRIBUTES: &[(&str, &str)] = &[
+    ("dependency", "string"),
+    ("redundant", "boolean"),
+    ("cycle", "boolean"),
+];
+
+/// The values of `NODE_ATTRIBUTES` for a node, leaving out those that do not apply to it.
+pub fn node_attributes(graph: &Graph, node: &Node) -> Vec<(&'static str, String)> {
+    [
+        ("kind", Some(node.kind.name().to_string())),
+        ("type", node.kind.type_name().map(str::to_string)),
+        ("package", graph.package_of(node).map(str::to_string)),
+        ("path", node.path.clone()),
+        ("sources", node.source_count.map(|count| count.to_string())),
+    ]
+    .into_iter()
+    .filter_map(|(name, value)| value.map(|value| (name, value)))
+    .collect()
+}
+
+/// The values of `EDGE_ATTRIBUTES` for an edge.
+pub fn edge_attributes(edge: &Edge) -> [(&'static str, String); 3] {
+    [
+        ("dependency", edge.kind.name().to_string()),
+        ("redundant", edge.redundant.to_string()),
+        ("cycle", edge.in_cycle.to_string()),
+    ]
+}
+
+pub fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}